@@ -1,29 +1,447 @@
-use chrono::{DateTime, Local};
+// Everything here is read from procfs and Linux specific libc calls, fail
+// early with a clear message instead of deep inside libc.
+#[cfg(not(target_os = "linux"))]
+compile_error!("this crate only supports Linux, it reads process information from /proc");
+
+use chrono::{DateTime, Local, Utc};
 use thiserror::Error;
 
 use std::{
-    ffi::CStr,
+    collections::{BTreeMap, HashMap, HashSet},
+    ffi::{CStr, CString},
     fmt,
     fs::DirEntry,
+    io::{self, Write},
+    net::{IpAddr, SocketAddr},
     os::unix::fs::MetadataExt,
-    path::PathBuf,
-    time::{Duration, SystemTime, UNIX_EPOCH},
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{Mutex, OnceLock, PoisonError},
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
     vec,
 };
 
 /// Represents a single process discovered under `/proc`.
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Process {
-    pid: u32,
+    pid: Pid,
+    /// Thread ID for the per-thread rows of [`ProcScanner::threads`].
+    tid: Option<Pid>,
+    ppid: Option<Pid>,
+    /// Process group (`pgrp`) and session IDs from `/proc/<pid>/stat`.
+    pgid: Option<Pid>,
+    sid: Option<Pid>,
+    /// Executable name (`comm`) from `/proc/<pid>/stat`.
+    name: Option<String>,
+    /// Raw contents of `/proc/<pid>/cmdline`, arguments are NUL separated.
     cmdline: Option<String>,
     binary_path: Option<PathBuf>,
+    /// The binary was deleted or replaced since the process started.
+    binary_deleted: bool,
     owner: Option<String>,
     start_time: Option<DateTime<Local>>,
-    state: Option<String>,
+    state: Option<ProcessState>,
+    /// Resident set size in bytes.
+    rss: Option<u64>,
+    /// Resident set size as a percentage of physical memory.
+    mem_percent: Option<f64>,
+    /// Virtual memory size in bytes.
+    vsz: Option<u64>,
+    /// Accumulated user and system CPU time, from `/proc/<pid>/stat`.
+    cpu_time: Option<Duration>,
+    /// Nanoseconds spent on the CPU, from `/proc/<pid>/schedstat`.
+    cpu_time_ns: Option<u64>,
+    /// Nanoseconds spent waiting on a run queue.
+    wait_time_ns: Option<u64>,
+    /// Number of timeslices run on the CPU.
+    timeslices: Option<u64>,
+    /// Scheduling policy (`SCHED_*`) from `/proc/<pid>/stat`.
+    policy: Option<u32>,
+    /// CPU the process last ran on (`processor` in `/proc/<pid>/stat`).
+    last_cpu: Option<u32>,
+    /// Kernel function the process is sleeping in, from `/proc/<pid>/wchan`.
+    wchan: Option<String>,
+    cgroup: Option<String>,
+    /// OOM killer badness from `/proc/<pid>/oom_score`, 0 to 2000.
+    oom_score: Option<i32>,
+    /// User adjustment of the OOM score, -1000 to 1000.
+    oom_score_adj: Option<i32>,
+    /// `NoNewPrivs` from `/proc/<pid>/status`.
+    no_new_privs: Option<bool>,
+    /// `Seccomp` from `/proc/<pid>/status`: 0 off, 1 strict, 2 filter.
+    seccomp_mode: Option<u8>,
+    /// Effective and permitted capability sets (`CapEff`, `CapPrm`).
+    cap_effective: Option<u64>,
+    cap_permitted: Option<u64>,
+    /// Bytes read from and written to storage, from `/proc/<pid>/io`.
+    read_bytes: Option<u64>,
+    write_bytes: Option<u64>,
+    /// Audit login UID from `/proc/<pid>/loginuid`, kept across setuid.
+    loginuid: Option<u32>,
+    /// [`loginuid`](Process::loginuid) resolved like `owner`.
+    login_user: Option<String>,
+    /// Target of the `/proc/<pid>/cwd` link.
+    cwd: Option<PathBuf>,
+    /// Number of entries in `/proc/<pid>/fd/`.
+    num_fd: Option<u32>,
+    /// Soft `Max open files` limit, `None` if unlimited.
+    fd_limit: Option<u64>,
+    /// Real, effective, saved and filesystem IDs from `Uid:` and `Gid:` in
+    /// `/proc/<pid>/status`.
+    uid: Option<[u32; 4]>,
+    gid: Option<[u32; 4]>,
+    /// `voluntary_ctxt_switches` and `nonvoluntary_ctxt_switches` from
+    /// `/proc/<pid>/status`.
+    voluntary_ctx_switches: Option<u64>,
+    nonvoluntary_ctx_switches: Option<u64>,
+    /// Page faults served from memory and ones that needed disk I/O, from
+    /// `minflt` and `majflt` in `/proc/<pid>/stat`.
+    minor_faults: Option<u64>,
+    major_faults: Option<u64>,
+    /// Address range of the text segment, `startcode` and `endcode` in
+    /// `/proc/<pid>/stat`.
+    start_code: Option<u64>,
+    end_code: Option<u64>,
+    /// When the proc files were read, for CPU usage between refreshes.
+    read_at: Option<Instant>,
+    /// The same moment as wall clock time, for [`Process::cpu_percent`] as of
+    /// the read when comparing snapshots.
+    read_time: Option<DateTime<Local>>,
+    /// CPU usage between the last two reads, see [`Process::refresh`].
+    recent_cpu_percent: Option<f64>,
+}
+
+/// The scheduling state of a process, as reported in `/proc/<pid>/status`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ProcessState {
+    /// `R`
+    Running,
+    /// `S`, interruptible sleep.
+    Sleeping,
+    /// `D`, uninterruptible sleep (usually I/O).
+    DiskSleep,
+    /// `Z`, terminated but not yet reaped by its parent.
+    Zombie,
+    /// `T`, stopped by a job control signal.
+    Stopped,
+    /// `t`, stopped by a debugger.
+    TracingStop,
+    /// `X`
+    Dead,
+    /// `I`, idle kernel thread.
+    Idle,
+    /// `P`
+    Parked,
+    /// `K`
+    Wakekill,
+    /// `W`
+    Waking,
+    /// Any state letter this crate doesn't know about.
+    Unknown(char),
+}
+
+/// Selects processes by several optional criteria, all of which must match.
+///
+/// Criteria left as `None` match every process. Processes missing the data a
+/// criterion needs (e.g. an unreadable RSS with `min_rss` set) don't match.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Filter {
+    /// Exact owner username (or numeric UID if it has no passwd entry).
+    pub owner: Option<String>,
+    /// Substring of the executable name.
+    pub name_substring: Option<String>,
+    pub state: Option<ProcessState>,
+    /// Minimum resident set size in bytes.
+    pub min_rss: Option<u64>,
+    /// Only these PIDs.
+    pub pid_in: Option<Vec<Pid>>,
+}
+
+/// A field to order processes by, see [`Process::cmp_by`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    Pid,
+    /// [`Process::cpu_percent`].
+    Cpu,
+    /// Resident set size.
+    Mem,
+    /// Executable name.
+    Name,
+}
+
+/// A process ID, guaranteed to be non-zero and within the system's `pid_max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Pid(u32);
+
+/// System-wide memory statistics from `/proc/meminfo`, in kilobytes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemInfo {
+    pub total_kb: u64,
+    pub available_kb: u64,
+    pub free_kb: u64,
+    pub buffers_kb: u64,
+    pub cached_kb: u64,
+    pub swap_total_kb: u64,
+    pub swap_free_kb: u64,
+}
+
+/// The fields of `/proc/<pid>/stat`, in file order (see `man 5 proc`).
+///
+/// Times are in clock ticks (`sysconf(_SC_CLK_TCK)`) and `rss` is in pages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcStat {
+    pub pid: u32,
+    /// Executable name, without the surrounding parentheses.
+    pub comm: String,
+    /// Single state character, e.g. `R`, `S` or `Z`.
+    pub state: char,
+    pub ppid: u32,
+    pub pgrp: u32,
+    pub session: u32,
+    /// Controlling terminal, encoded as a device number.
+    pub tty_nr: i32,
+    pub tpgid: i32,
+    pub flags: u32,
+    pub minflt: u64,
+    pub cminflt: u64,
+    pub majflt: u64,
+    pub cmajflt: u64,
+    pub utime: u64,
+    pub stime: u64,
+    pub cutime: i64,
+    pub cstime: i64,
+    pub priority: i64,
+    pub nice: i64,
+    pub num_threads: i64,
+    pub itrealvalue: i64,
+    /// Time the process started after system boot, in clock ticks.
+    pub starttime: u64,
+    /// Virtual memory size in bytes.
+    pub vsize: u64,
+    /// Resident set size in pages.
+    pub rss: i64,
+    pub rsslim: u64,
+    pub startcode: u64,
+    pub endcode: u64,
+    pub startstack: u64,
+    pub kstkesp: u64,
+    pub kstkeip: u64,
+    pub signal: u64,
+    pub blocked: u64,
+    pub sigignore: u64,
+    pub sigcatch: u64,
+    pub wchan: u64,
+    pub nswap: u64,
+    pub cnswap: u64,
+    pub exit_signal: i32,
+    /// CPU the process last ran on.
+    pub processor: i32,
+    pub rt_priority: u32,
+    pub policy: u32,
+}
+
+/// The fields of `/proc/<pid>/status` (see `man 5 proc`), `None` for lines
+/// missing on older kernels or with values that don't parse.
+///
+/// Memory sizes are in kilobytes, like the file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcStatus {
+    /// Executable name, truncated to 15 bytes like `comm` in `stat`.
+    pub name: Option<String>,
+    pub state: Option<ProcessState>,
+    /// Thread group ID, the PID of the process a thread belongs to.
+    pub tgid: Option<u32>,
+    pub pid: Option<u32>,
+    pub ppid: Option<u32>,
+    /// Real, effective, saved and filesystem IDs.
+    pub uid: Option<[u32; 4]>,
+    pub gid: Option<[u32; 4]>,
+    pub threads: Option<u32>,
+    /// Peak and current virtual memory size.
+    pub vm_peak_kb: Option<u64>,
+    pub vm_size_kb: Option<u64>,
+    /// Peak and current resident set size.
+    pub vm_hwm_kb: Option<u64>,
+    pub vm_rss_kb: Option<u64>,
+    pub vm_swap_kb: Option<u64>,
+    /// Capability sets (`CapInh`, `CapPrm`, `CapEff`, `CapBnd`).
+    pub cap_inheritable: Option<u64>,
+    pub cap_permitted: Option<u64>,
+    pub cap_effective: Option<u64>,
+    pub cap_bounding: Option<u64>,
+    pub no_new_privs: Option<bool>,
+    /// 0 off, 1 strict, 2 filter.
+    pub seccomp: Option<u8>,
+    /// CPUs the process may run on, e.g. `0-3,8`.
+    pub cpus_allowed_list: Option<String>,
+    pub voluntary_ctxt_switches: Option<u64>,
+    pub nonvoluntary_ctxt_switches: Option<u64>,
+    /// Every `key: value` line with the value trimmed, including the ones
+    /// parsed into the fields above.
+    pub fields: BTreeMap<String, String>,
+}
+
+/// One resource limit from `/proc/<pid>/limits`, like a line of `ulimit -a`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProcessLimit {
+    /// e.g. `Max open files`.
+    pub name: String,
+    /// Soft limit, `None` when unlimited.
+    pub soft: Option<u64>,
+    /// Hard limit, `None` when unlimited.
+    pub hard: Option<u64>,
+    /// e.g. `files` or `bytes`, empty for unitless limits.
+    pub units: String,
+}
+
+/// Transport protocol of a [`NetConnection`], and which `/proc/<pid>/net/`
+/// table it came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Proto {
+    Tcp,
+    Tcp6,
+    Udp,
+    Udp6,
+}
+
+/// Socket state from the `st` column of `/proc/<pid>/net/*`.
+///
+/// UDP sockets reuse these codes, unconnected ones are reported as `Close`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TcpState {
+    Established,
+    SynSent,
+    SynRecv,
+    FinWait1,
+    FinWait2,
+    TimeWait,
+    Close,
+    CloseWait,
+    LastAck,
+    Listen,
+    Closing,
+    NewSynRecv,
+    Unknown(u8),
+}
+
+/// An open socket of a process, like a line of `ss -p`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetConnection {
+    pub proto: Proto,
+    pub local_addr: SocketAddr,
+    /// All zeroes for listening or unconnected sockets.
+    pub remote_addr: SocketAddr,
+    pub state: TcpState,
+    /// Socket inode, matching the `socket:[<inode>]` links in `/proc/<pid>/fd/`.
+    pub inode: u64,
+}
+
+/// Aggregate resource usage of a group of processes. Unknown values count
+/// as zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GroupSummary {
+    /// Number of processes in the group.
+    pub count: usize,
+    /// Sum of the resident set sizes in bytes.
+    pub total_rss: u64,
+    /// Sum of the accumulated CPU times.
+    pub total_cpu_time: Duration,
+    /// Sum of [`Process::cpu_percent`], can exceed 100 on multi-core systems.
+    pub total_cpu_percent: f64,
+}
+
+/// The processes of one owner with their combined resource usage, see
+/// [`group_by_owner`].
+#[derive(Debug, Clone)]
+pub struct ProcessGroup {
+    /// Username, numeric UID if it couldn't be resolved, or `-` if unknown.
+    pub owner: String,
+    pub processes: Vec<Process>,
+    pub summary: GroupSummary,
+}
+
+/// A [`ProcessTable`] with rows highlighted by state on a terminal: zombies
+/// red, processes using a lot of CPU or memory yellow and running ones green.
+///
+/// Coloring needs the `color` feature (on by default). Without it
+/// [`color`](ProcessListDisplay::color) has no effect and the plain table is
+/// written.
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessListDisplay<'a> {
+    table: ProcessTable<'a>,
+    color: bool,
+}
+
+/// Processes arranged under their parents, printed like `pstree`.
+///
+/// Processes whose parent isn't in the set, e.g. because it exited, become
+/// additional roots next to `init`.
+#[derive(Debug, Clone)]
+pub struct ProcessTree<'a> {
+    processes: &'a [Process],
+    /// Indices into `processes` of the children of each parent PID.
+    children: HashMap<Pid, Vec<usize>>,
+    roots: Vec<usize>,
+}
+
+/// A snapshot of processes indexed by PID, for lookups and comparing
+/// snapshots without searching a `Vec`.
+///
+/// Holds one process per PID, so for the rows of [`ProcScanner::threads`]
+/// only the last thread of each process is kept.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessSet(HashMap<Pid, Process>);
+
+/// What changed between two [`ProcessSet`] snapshots, see
+/// [`ProcessSet::diff`]. All lists are sorted by PID.
+///
+/// A PID that was reused by a new process in between shows up in both
+/// `added` and `removed`.
+#[derive(Debug, Clone, Default)]
+pub struct ProcessDiff {
+    /// Processes only in the newer snapshot.
+    pub added: Vec<Process>,
+    /// Processes only in the older snapshot, i.e. the ones that exited.
+    pub removed: Vec<Process>,
+    /// `(old, new)` for processes in both whose state or CPU usage changed.
+    /// CPU usage is compared as [`Process::cpu_percent`] at the time each
+    /// snapshot was read, to one decimal as printed, so an idle process
+    /// doesn't count as changed just because its lifetime average drifts.
+    pub changed_state: Vec<(Process, Process)>,
+}
+
+/// Configures where processes are read from and which optional fields are
+/// read while scanning `/proc`.
+///
+/// Every option costs an extra file read per process, so they are all
+/// disabled by default. [`get_processes`] is equivalent to
+/// `ProcScanner::new().scan()`.
+///
+/// A captured `/proc` snapshot can be analysed offline with
+/// `ProcScanner::with_root("/tmp/proc-snapshot").scan()`.
+#[derive(Debug, Clone)]
+pub struct ProcScanner {
+    root: Option<PathBuf>,
+    filter: Option<Filter>,
+    wchan: bool,
+    cgroup: bool,
+    threads: bool,
+    io: bool,
+    loginuid: bool,
+    cwd: bool,
+    fds: bool,
+    /// Extra attempts at reading `stat` and `status` after `NotFound`.
+    retries: u32,
 }
 
 /// Errors that can occur when reading or parsing process information.
+///
+/// New variants may be added as the crate grows, so matches need a
+/// wildcard arm.
 #[derive(Error, Debug)]
+#[non_exhaustive]
 pub enum PsError {
     /// Generic I/O error while reading from `/proc`.
     #[error("I/O error")]
@@ -37,217 +455,4182 @@ pub enum PsError {
     #[error("Failed to parse as float")]
     FailedToParseAsFloat(#[from] std::num::ParseFloatError),
 
+    /// Failed to parse integer values (e.g., meminfo sizes).
+    #[error("Failed to parse as integer")]
+    FailedToParseAsInt(#[from] std::num::ParseIntError),
+
+    /// A required key was missing from `/proc/meminfo`.
+    #[error("Missing field in meminfo: {0}")]
+    MissingMemInfoField(&'static str),
+
+    /// A string or number that isn't a valid PID.
+    #[error("Invalid PID: {0}")]
+    InvalidPid(String),
+
+    /// A name that isn't one of [`Column::name`].
+    #[error("Unknown field: {0}")]
+    UnknownField(String),
+
+    /// Not allowed to access a process, e.g. reading another user's
+    /// `/proc/<pid>/stack` without `CAP_SYS_PTRACE`. `file` is the proc file
+    /// that couldn't be read, if any.
+    #[error(
+        "Permission denied for process {pid}{}",
+        file.map(|file| format!(" reading {file}")).unwrap_or_default()
+    )]
+    PermissionDenied {
+        pid: Pid,
+        file: Option<&'static str>,
+    },
+
+    /// The process exited before it could be acted on.
+    #[error("Process {0} no longer exists")]
+    ProcessGone(Pid),
+
+    /// A `/proc` entry that isn't a process directory, e.g. `/proc/bus`.
+    #[error("Not a process: {0}")]
+    NotAProcess(String),
+
+    /// A line of `/proc/<pid>/net/*` isn't in the expected format.
+    #[error("Malformed network table entry")]
+    MalformedNetEntry,
+
+    /// `/proc/<pid>/limits` doesn't start with the expected header.
+    #[error("Malformed limits file")]
+    MalformedLimits,
+
+    /// `/proc/<pid>/stat` is truncated or not in the expected format.
+    #[error("Malformed stat file")]
+    MalformedStat,
+
     /// Failed to get the current system time.
     #[error("Failed to get system time")]
     FailedToGetSystemTime(#[from] std::time::SystemTimeError),
 
+    /// The blocking task behind [`get_processes_async`] panicked or was cancelled.
+    #[cfg(feature = "tokio")]
+    #[error("Blocking task failed")]
+    BlockingTaskFailed(#[from] tokio::task::JoinError),
+
     /// Failed to read the system clock tick rate via `sysconf`.
     #[error("Failed to get system clock tick rate: {0}")]
     FailedToGetSysClockTickRate(i32),
+
+    /// `kill` failed for another reason than the process being gone or
+    /// not ours, e.g. an invalid signal number.
+    #[error("Failed to send signal: {0}")]
+    FailedToSendSignal(i32),
 }
 
-/// Extracts the process state from `/proc/<pid>/status`.
-///
-/// Looks for a line starting with `State:` and returns the status
-/// string (e.g., `"S (sleeping)"`).
-fn find_state(status: &str) -> Option<String> {
-    for line in status.lines() {
-        if line.starts_with("State:") {
-            // Map allows safety.
-            let process_state = line.split_once('\t').map(|x| x.1);
-            return process_state.map(|s| s.to_string());
+/// Environment variable pointing at an alternate proc mount.
+pub const PROC_ROOT_ENV: &str = "PROC_ROOT";
+
+/// Returns the proc mount point, `$PROC_ROOT` if set and `/proc` otherwise.
+fn proc_root() -> PathBuf {
+    std::env::var_os(PROC_ROOT_ENV)
+        .map(PathBuf::from)
+        .unwrap_or_else(|| PathBuf::from("/proc"))
+}
+
+/// Upper bound on `pid_max` on 64-bit kernels (`PID_MAX_LIMIT`).
+const PID_MAX_LIMIT: u32 = 4_194_304;
+
+/// Returns the system's `pid_max`, read once from `/proc/sys/kernel/pid_max`
+/// and falling back to [`PID_MAX_LIMIT`] if it's unavailable.
+fn pid_max() -> u32 {
+    static PID_MAX: OnceLock<u32> = OnceLock::new();
+    *PID_MAX.get_or_init(|| {
+        std::fs::read_to_string(proc_root().join("sys/kernel/pid_max"))
+            .ok()
+            .and_then(|pid_max| pid_max.trim().parse().ok())
+            .unwrap_or(PID_MAX_LIMIT)
+    })
+}
+
+impl Pid {
+    /// Returns `None` for zero or values from the system's `pid_max` on,
+    /// which is one more than the largest PID the kernel assigns.
+    pub fn new(val: u32) -> Option<Pid> {
+        (val != 0 && val < pid_max()).then_some(Pid(val))
+    }
+
+    /// The PID as a plain integer.
+    pub fn get(self) -> u32 {
+        self.0
+    }
+}
+
+impl fmt::Display for Pid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        // Forward so width and alignment flags apply.
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+impl FromStr for Pid {
+    type Err = PsError;
+
+    fn from_str(pid: &str) -> Result<Self, Self::Err> {
+        pid.parse()
+            .ok()
+            .and_then(Pid::new)
+            .ok_or_else(|| PsError::InvalidPid(pid.to_owned()))
+    }
+}
+
+impl ProcessState {
+    /// Maps a state letter from `stat` or `status` to a [`ProcessState`].
+    pub fn from_char(state: char) -> Self {
+        match state {
+            'R' => Self::Running,
+            'S' => Self::Sleeping,
+            'D' => Self::DiskSleep,
+            'Z' => Self::Zombie,
+            'T' => Self::Stopped,
+            't' => Self::TracingStop,
+            'X' | 'x' => Self::Dead,
+            'I' => Self::Idle,
+            'P' => Self::Parked,
+            'K' => Self::Wakekill,
+            'W' => Self::Waking,
+            other => Self::Unknown(other),
+        }
+    }
+
+    /// The state letter, as shown in the `STAT` column of `ps`.
+    pub fn as_char(self) -> char {
+        match self {
+            Self::Running => 'R',
+            Self::Sleeping => 'S',
+            Self::DiskSleep => 'D',
+            Self::Zombie => 'Z',
+            Self::Stopped => 'T',
+            Self::TracingStop => 't',
+            Self::Dead => 'X',
+            Self::Idle => 'I',
+            Self::Parked => 'P',
+            Self::Wakekill => 'K',
+            Self::Waking => 'W',
+            Self::Unknown(other) => other,
+        }
+    }
+
+    /// The description the kernel uses in `/proc/<pid>/status`.
+    fn description(self) -> Option<&'static str> {
+        match self {
+            Self::Running => Some("running"),
+            Self::Sleeping => Some("sleeping"),
+            Self::DiskSleep => Some("disk sleep"),
+            Self::Zombie => Some("zombie"),
+            Self::Stopped => Some("stopped"),
+            Self::TracingStop => Some("tracing stop"),
+            Self::Dead => Some("dead"),
+            Self::Idle => Some("idle"),
+            Self::Parked => Some("parked"),
+            Self::Wakekill => Some("wakekill"),
+            Self::Waking => Some("waking"),
+            Self::Unknown(_) => None,
         }
     }
-    None
 }
 
-/// Pretty-print implementation for `Process`.
-impl fmt::Display for Process {
+/// Formats the state like the kernel does, e.g. `S (sleeping)`.
+impl fmt::Display for ProcessState {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        if f.alternate() {
-            writeln!(
-                f,
-                "{:<10} {:<15} {:<15} {:<30} {:<20} {:<15}",
-                "PID", "Owner", "Cmdline", "Binary Path", "Start Time", "State",
-            )?;
+        match self.description() {
+            // Note: pad respects width and alignment flags, write! doesn't.
+            Some(description) => f.pad(&format!("{} ({description})", self.as_char())),
+            None => f.pad(&self.as_char().to_string()),
         }
+    }
+}
 
-        let start_time = match self.start_time {
-            // Format the datetime as a normal readable string.
-            Some(date_time) => date_time.format("%Y-%m-%d %H:%M:%S").to_string(),
-            None => "unknown".to_owned(),
-        };
+impl Filter {
+    /// Returns `true` if `pid` can match, checked before a process is read.
+    fn matches_pid(&self, pid: Pid) -> bool {
+        self.pid_in.as_ref().is_none_or(|pids| pids.contains(&pid))
+    }
 
-        writeln!(
-            f,
-            "{:<10} {:<15} {:<15} {:<30}      {:<20} {:<15}",
-            self.pid,
-            self.owner.as_deref().unwrap_or("-"),
-            self.cmdline.as_deref().unwrap_or("-"),
-            self.binary_path
-                .clone()
-                // Pathbuf implements default so can use unwrap_or_
-                .unwrap_or_default()
-                .to_string_lossy(),
-            start_time,
-            self.state.as_deref().unwrap_or("-")
-        )
+    /// Returns `true` if the process matches every criterion that is set.
+    pub fn matches(&self, p: &Process) -> bool {
+        self.matches_pid(p.pid)
+            && self
+                .owner
+                .as_deref()
+                .is_none_or(|owner| p.owner() == Some(owner))
+            && self
+                .name_substring
+                .as_deref()
+                .is_none_or(|name| p.name().is_some_and(|n| n.contains(name)))
+            && self.state.is_none_or(|state| p.state == Some(state))
+            && self
+                .min_rss
+                .is_none_or(|min_rss| p.rss.is_some_and(|rss| rss >= min_rss))
     }
 }
 
-/// Returns the start time of a process by reading `/proc/uptime` and `/proc/<pid>/stat`.
+/// Returns the value of the `State:` line from the contents of a
+/// `/proc/<pid>/status` file, e.g. `"S (sleeping)"`.
 ///
-/// * `uptime_path` — Path to `/proc/uptime`  
-/// * `stat_path` — Path to `/proc/<pid>/stat`  
-/// * `system_clock_tick_rate` — Clock ticks per second from `sysconf(_SC_CLK_TCK)`
+/// A state line is the label `State`, a colon, then the state letter and
+/// its name in parentheses:
 ///
-/// Note: functions that don't need ownership take reference.
-fn get_start_time(
-    uptime_path: &PathBuf,
-    stat_path: &PathBuf,
-    system_clock_tick_rate: f64,
-) -> Result<DateTime<Local>, PsError> {
-    let uptime_res = std::fs::read_to_string(uptime_path)?;
+/// ```text
+/// State:\tS (sleeping)
+/// ```
+///
+/// The kernel separates the label from the value with a tab, but some pad
+/// with spaces, so surrounding whitespace is trimmed. Only a line starting
+/// exactly with `State:` counts, other lines are ignored. Returns `None` if
+/// there's no such line or its value is empty. The first character of the
+/// result can be turned into a [`ProcessState`] with
+/// [`ProcessState::from_char`].
+pub fn parse_state_from_status(status: &str) -> Option<String> {
+    find_status_field(status, "State").map(str::to_owned)
+}
 
-    let uptime_seconds: f64 = uptime_res
-        .split_whitespace()
-        .next()
-        // ok_or checks some (if there is a value) if not errors.
-        .ok_or(PsError::FailedToGetUptimeFromStat)?
-        // tries to turn "48267.42" into f64.
-        .parse()?;
+impl ProcStatus {
+    /// Parses the contents of `/proc/<pid>/status`.
+    ///
+    /// Lines look like `State:\tS (sleeping)`. The label is usually followed
+    /// by a tab, but some kernels pad with spaces instead, so values are
+    /// trimmed. Lines without a `:` are skipped, this never fails.
+    pub fn parse(status: &str) -> ProcStatus {
+        let fields: BTreeMap<String, String> = status
+            .lines()
+            .filter_map(|line| line.split_once(':'))
+            .map(|(key, value)| (key.to_owned(), value.trim().to_owned()))
+            .collect();
 
-    let stat = std::fs::read_to_string(stat_path)?;
-    let stats: Vec<&str> = stat.split_whitespace().collect();
+        let field = |key: &str| {
+            fields
+                .get(key)
+                .map(String::as_str)
+                .filter(|value| !value.is_empty())
+        };
+        fn number<T: FromStr>(value: Option<&str>) -> Option<T> {
+            value?.parse().ok()
+        }
+        // Sizes are printed as e.g. `1234 kB`.
+        let kb = |key| field(key)?.strip_suffix("kB")?.trim().parse().ok();
+        // Capability sets are printed as 16 hex digits.
+        let capabilities = |key| u64::from_str_radix(field(key)?, 16).ok();
+        // e.g. `Uid:\t1000\t0\t0\t0` for a setuid root binary run by UID 1000.
+        let ids = |key| {
+            let mut ids = field(key)?.split_whitespace();
+            let mut next = || ids.next()?.parse().ok();
+            Some([next()?, next()?, next()?, next()?])
+        };
 
-    // start_time is at the 22nd column.
-    let time_stat_str = stats[21];
-    let time_stat: f64 = time_stat_str.parse()?;
-    // convert start_time to seconds since boot.
-    let start_time_in_seconds = time_stat / system_clock_tick_rate;
-    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs_f64();
-    let boot_time = now - uptime_seconds;
-    let process_start_time = (boot_time + start_time_in_seconds) as u64;
+        ProcStatus {
+            name: field("Name").map(str::to_owned),
+            state: parse_state_from_status(status)
+                .and_then(|state| state.chars().next())
+                .map(ProcessState::from_char),
+            tgid: number(field("Tgid")),
+            pid: number(field("Pid")),
+            ppid: number(field("PPid")),
+            uid: ids("Uid"),
+            gid: ids("Gid"),
+            threads: number(field("Threads")),
+            vm_peak_kb: kb("VmPeak"),
+            vm_size_kb: kb("VmSize"),
+            vm_hwm_kb: kb("VmHWM"),
+            vm_rss_kb: kb("VmRSS"),
+            vm_swap_kb: kb("VmSwap"),
+            cap_inheritable: capabilities("CapInh"),
+            cap_permitted: capabilities("CapPrm"),
+            cap_effective: capabilities("CapEff"),
+            cap_bounding: capabilities("CapBnd"),
+            no_new_privs: field("NoNewPrivs").map(|value| value == "1"),
+            seccomp: number(field("Seccomp")),
+            cpus_allowed_list: field("Cpus_allowed_list").map(str::to_owned),
+            voluntary_ctxt_switches: number(field("voluntary_ctxt_switches")),
+            nonvoluntary_ctxt_switches: number(field("nonvoluntary_ctxt_switches")),
+            fields,
+        }
+    }
+}
 
-    let duration = Duration::from_secs(process_start_time);
-    let system_time = UNIX_EPOCH + duration;
-    let date_time: DateTime<Local> = system_time.into();
+/// Returns the value of the `label:` line of a `/proc` file in the format of
+/// `/proc/<pid>/status`, e.g. `/proc/meminfo`.
+fn find_status_field<'a>(status: &'a str, label: &str) -> Option<&'a str> {
+    for line in status.lines() {
+        // The label is usually followed by a tab, but some kernels pad with
+        // spaces instead, so take whatever comes after the whitespace.
+        if let Some(value) = line
+            .strip_prefix(label)
+            .and_then(|rest| rest.strip_prefix(':'))
+        {
+            let value = value.trim();
+            return (!value.is_empty()).then_some(value);
+        }
+    }
+    None
+}
 
-    Ok(date_time)
+impl ProcStat {
+    /// Parses the contents of `/proc/<pid>/stat`.
+    ///
+    /// The command name in the 2nd field is wrapped in parentheses and may
+    /// itself contain spaces or parentheses, so it spans from the first `(` to
+    /// the last `)`. Fields after `policy` (41st) are ignored.
+    pub fn parse(stat: &str) -> Result<ProcStat, PsError> {
+        let (pid, rest) = stat.split_once('(').ok_or(PsError::MalformedStat)?;
+        let (comm, rest) = rest.rsplit_once(')').ok_or(PsError::MalformedStat)?;
+
+        let mut fields = rest.split_whitespace();
+        let mut next = || fields.next().ok_or(PsError::MalformedStat);
+
+        // Note: struct fields are evaluated in the order they are written.
+        Ok(ProcStat {
+            pid: pid.trim().parse()?,
+            comm: comm.to_owned(),
+            state: next()?.chars().next().ok_or(PsError::MalformedStat)?,
+            ppid: next()?.parse()?,
+            pgrp: next()?.parse()?,
+            session: next()?.parse()?,
+            tty_nr: next()?.parse()?,
+            tpgid: next()?.parse()?,
+            flags: next()?.parse()?,
+            minflt: next()?.parse()?,
+            cminflt: next()?.parse()?,
+            majflt: next()?.parse()?,
+            cmajflt: next()?.parse()?,
+            utime: next()?.parse()?,
+            stime: next()?.parse()?,
+            cutime: next()?.parse()?,
+            cstime: next()?.parse()?,
+            priority: next()?.parse()?,
+            nice: next()?.parse()?,
+            num_threads: next()?.parse()?,
+            itrealvalue: next()?.parse()?,
+            starttime: next()?.parse()?,
+            vsize: next()?.parse()?,
+            rss: next()?.parse()?,
+            rsslim: next()?.parse()?,
+            startcode: next()?.parse()?,
+            endcode: next()?.parse()?,
+            startstack: next()?.parse()?,
+            kstkesp: next()?.parse()?,
+            kstkeip: next()?.parse()?,
+            signal: next()?.parse()?,
+            blocked: next()?.parse()?,
+            sigignore: next()?.parse()?,
+            sigcatch: next()?.parse()?,
+            wchan: next()?.parse()?,
+            nswap: next()?.parse()?,
+            cnswap: next()?.parse()?,
+            exit_signal: next()?.parse()?,
+            processor: next()?.parse()?,
+            rt_priority: next()?.parse()?,
+            policy: next()?.parse()?,
+        })
+    }
+}
+
+impl FromStr for ProcStat {
+    type Err = PsError;
+
+    fn from_str(stat: &str) -> Result<Self, Self::Err> {
+        ProcStat::parse(stat)
+    }
 }
 
-/// Attempts to parse a single process directory into a [`Process`] struct.
+/// The commonly used subset of [`ProcStat`], see [`parse_stat_fields`].
 ///
-/// This reads values from several `/proc/<pid>/...` files:
-/// - `cmdline`  
-/// - `exe` (symlink)  
-/// - `stat` (start time)  
-/// - `status` (state)  
-/// - directory metadata (UID → username)
+/// Fields have the same types as in [`ProcStat`], following `man 5 proc`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatFields {
+    pub comm: String,
+    pub state: char,
+    pub ppid: u32,
+    pub pgrp: u32,
+    pub session: u32,
+    /// Controlling terminal, encoded as a device number. Signed (`%d`) since
+    /// the kernel prints it from a signed int.
+    pub tty_nr: i32,
+    /// Scheduling priority and nice value, `%ld` in the kernel. The priority
+    /// is negative for real-time tasks and nice ranges from -20 to 19.
+    pub priority: i64,
+    pub nice: i64,
+    pub num_threads: i64,
+    /// Time the process started after system boot, in clock ticks.
+    pub start_time: u64,
+    /// CPU time in user and kernel mode, in clock ticks.
+    pub utime: u64,
+    pub stime: u64,
+    /// Virtual memory size in bytes.
+    pub vsize: u64,
+    /// Resident set size in pages.
+    pub rss: i64,
+}
+
+impl From<ProcStat> for StatFields {
+    fn from(stat: ProcStat) -> Self {
+        StatFields {
+            comm: stat.comm,
+            state: stat.state,
+            ppid: stat.ppid,
+            pgrp: stat.pgrp,
+            session: stat.session,
+            tty_nr: stat.tty_nr,
+            priority: stat.priority,
+            nice: stat.nice,
+            num_threads: stat.num_threads,
+            start_time: stat.starttime,
+            utime: stat.utime,
+            stime: stat.stime,
+            vsize: stat.vsize,
+            rss: stat.rss,
+        }
+    }
+}
+
+/// Parses the contents of `/proc/<pid>/stat` into its commonly used fields.
 ///
-/// Returns `None` if the directory name is not a PID or if the process
-/// disappears during parsing.
-fn get_process(dir_ent: DirEntry, system_clock_tick_rate: f64) -> Option<Process> {
-    let path = "/proc";
-    // Only parse filenames if they are numbers (process').
-    match dir_ent.file_name().to_string_lossy().parse::<u32>() {
-        Ok(filename) => {
-            let cmdline = format!("{path}/{filename}/cmdline");
-            let binary_path = format!("{path}/{filename}/exe");
-            let stat_path = PathBuf::from(format!("{path}/{filename}/stat"));
-            let uptime_path = PathBuf::from(format!("{path}/uptime"));
-            let state_path = format!("{path}/{filename}/status");
-
-            let mut process = Process {
-                pid: filename,
-                cmdline: None,
-                binary_path: None,
-                owner: None,
-                start_time: None,
-                state: None,
-            };
+/// Same as [`ProcStat::parse`], a missing or non-numeric field is a
+/// [`PsError::MalformedStat`] or [`PsError::FailedToParseAsInt`] instead of a panic.
+pub fn parse_stat_fields(stat: &str) -> Result<StatFields, PsError> {
+    ProcStat::parse(stat).map(StatFields::from)
+}
 
-            // Read command line.
-            if let Ok(cmd) = std::fs::read_to_string(cmdline) {
-                process.cmdline = Some(cmd);
-            }
-
-            // Read executable symlink.
-            // Note: .ok() converts result into success case or None
-            process.binary_path = std::fs::read_link(binary_path).ok();
-
-            // Extract owner name from UID.
-            if let Ok(metadata) = dir_ent.metadata() {
-                let owner_id = metadata.uid();
-                let owner = unsafe {
-                    // getpwuid_r is thread safe because we provide our own buffer
-                    // Will return null if no matching entry
-                    let res = libc::getpwuid(owner_id);
-                    if res.is_null() {
-                        Some(owner_id.to_string())
-                    } else {
-                        let passwd = *res;
-                        // Construct rust string from raw pointer
-                        let owner = CStr::from_ptr(passwd.pw_name);
-                        // Note: to_string_lossy converts the bytes it can to string or gives up
-                        Some(owner.to_string_lossy().to_string())
-                    }
-                };
-                process.owner = owner;
-            }
+/// Formats a byte count using binary multiples, e.g. `12.3M` or `1.1G`.
+///
+/// Counts below 1 KiB are printed as-is with a `B` suffix.
+pub fn format_bytes(n: u64) -> String {
+    const UNITS: [char; 6] = ['K', 'M', 'G', 'T', 'P', 'E'];
+    if n < 1024 {
+        return format!("{n}B");
+    }
 
-            // Start time.
-            match get_start_time(&uptime_path, &stat_path, system_clock_tick_rate) {
-                Ok(date_time) => process.start_time = Some(date_time),
-                Err(e) => eprintln!("{}", e),
-            }
+    let mut value = n as f64 / 1024.0;
+    let mut unit = 0;
+    // Move up a unit if rounding to one decimal would print e.g. `1024.0K`.
+    while value >= 1023.95 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1}{}", UNITS[unit])
+}
 
-            // Process state.
-            if let Ok(state_res) = std::fs::read_to_string(state_path) {
-                let process_state = find_state(&state_res);
-                process.state = process_state;
-            }
+/// Formats CPU time like the `TIME` column of `ps`: `[dd-]hh:mm:ss`.
+fn format_cpu_time(cpu_time: Duration) -> String {
+    let seconds = cpu_time.as_secs();
+    let (days, hours) = (seconds / 86_400, seconds / 3600 % 24);
+    let (minutes, seconds) = (seconds / 60 % 60, seconds % 60);
+    if days > 0 {
+        format!("{days}-{hours:02}:{minutes:02}:{seconds:02}")
+    } else {
+        format!("{hours:02}:{minutes:02}:{seconds:02}")
+    }
+}
+
+/// Parses the three counters in `/proc/<pid>/schedstat`: CPU time and run
+/// queue wait time in nanoseconds, and the number of timeslices.
+fn find_schedstat(schedstat: &str) -> Option<(u64, u64, u64)> {
+    let mut fields = schedstat.split_whitespace().map(|field| field.parse().ok());
+    Some((fields.next()??, fields.next()??, fields.next()??))
+}
+
+/// Parses `/proc/<pid>/wchan`, which reads `0` while the process is running.
+fn find_wchan(wchan: &str) -> Option<String> {
+    let wchan = wchan.trim();
+    (!wchan.is_empty() && wchan != "0").then(|| wchan.to_string())
+}
 
-            Some(process)
+/// Extracts `read_bytes` and `write_bytes` from `/proc/<pid>/io`.
+///
+/// Unlike `rchar` and `wchar` these only count I/O that reached the storage
+/// layer, not reads served from the page cache.
+fn find_io_bytes(io: &str) -> Option<(u64, u64)> {
+    let (mut read_bytes, mut write_bytes) = (None, None);
+    for line in io.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        match key {
+            "read_bytes" => read_bytes = value.trim().parse().ok(),
+            "write_bytes" => write_bytes = value.trim().parse().ok(),
+            _ => {}
         }
+    }
+    Some((read_bytes?, write_bytes?))
+}
 
-        Err(_) => None,
+/// Splits the ` (deleted)` suffix the kernel appends to `/proc/<pid>/exe`
+/// when the binary no longer exists off the path.
+fn strip_deleted_suffix(binary_path: PathBuf) -> (PathBuf, bool) {
+    match binary_path
+        .to_str()
+        .and_then(|path| path.strip_suffix(" (deleted)"))
+    {
+        Some(path) => (PathBuf::from(path), true),
+        None => (binary_path, false),
     }
 }
 
-/// Reads and returns all processes from `/proc`, similar to `ps aux`.
-///
-/// This function:
-/// 1. Reads `/proc`  
-/// 2. Determines the system clock tick rate (`sysconf(_SC_CLK_TCK)`)  
-/// 3. Iterates over all numeric directories  
-/// 4. Attempts to parse them into [`Process`] structs  
+/// Reads a proc file holding a single integer, like `/proc/<pid>/oom_score`.
+fn read_int<T: FromStr>(path: &Path) -> Option<T> {
+    std::fs::read_to_string(path).ok()?.trim().parse().ok()
+}
+
+/// Extracts the cgroup v2 path from `/proc/<pid>/cgroup`.
 ///
-/// Returns a vector of all successfully parsed processes.
-pub fn get_processes() -> Result<Vec<Process>, PsError> {
-    let res = std::fs::read_dir("/proc").unwrap();
+/// The unified hierarchy is the line starting with `0::`, e.g.
+/// `0::/system.slice/docker-<id>.scope`. Returns `None` on v1-only systems.
+fn find_cgroup(cgroup: &str) -> Option<String> {
+    cgroup
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .map(|path| path.to_string())
+}
 
-    // This is just for linux, windows might have an easier way of accessing this information.
-    let system_clock_tick_rate = unsafe { libc::sysconf(libc::_SC_CLK_TCK) } as f64;
-    // If libc returns -1 that option does exist
-    if system_clock_tick_rate == -1.0 {
-        // This is not thread safe
-        let err_num = unsafe { *libc::__errno_location() };
-        return Err(PsError::FailedToGetSysClockTickRate(err_num));
+impl Process {
+    /// The process ID.
+    pub fn pid(&self) -> Pid {
+        self.pid
     }
 
-    let mut vec_of_processs = vec![];
-    for content in res {
-        let content = content.unwrap();
-        // Only want directories
-        if !content.path().is_dir() {
-            continue;
+    /// The thread ID, if this row is a single thread of the process as read
+    /// with [`ProcScanner::threads`]. The main thread's ID equals the PID.
+    pub fn tid(&self) -> Option<Pid> {
+        self.tid
+    }
+
+    /// The parent PID, read from `/proc/<pid>/stat`. `None` for processes
+    /// without a parent, such as `init` and `kthreadd`.
+    pub fn ppid(&self) -> Option<Pid> {
+        self.ppid
+    }
+
+    /// The process group ID, shared by the processes of a shell pipeline.
+    /// `None` for kernel threads, which report 0.
+    pub fn pgid(&self) -> Option<Pid> {
+        self.pgid
+    }
+
+    /// The session ID, shared by everything started from one login shell.
+    /// `None` for kernel threads, which report 0.
+    pub fn sid(&self) -> Option<Pid> {
+        self.sid
+    }
+
+    /// The executable name, as shown by `ps -o comm` (at most 15 characters).
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The username owning the process, or its numeric UID if it has no
+    /// passwd entry.
+    pub fn owner(&self) -> Option<&str> {
+        self.owner.as_deref()
+    }
+
+    /// The scheduling state from `/proc/<pid>/status`.
+    pub fn state(&self) -> Option<ProcessState> {
+        self.state
+    }
+
+    /// When the process was started, in the local timezone.
+    pub fn start_time(&self) -> Option<DateTime<Local>> {
+        self.start_time
+    }
+
+    /// When the process was started, in UTC. Handy for comparing output
+    /// collected from machines in different timezones.
+    pub fn start_time_utc(&self) -> Option<DateTime<Utc>> {
+        self.start_time
+            .map(|date_time| date_time.with_timezone(&Utc))
+    }
+
+    /// Shorthand for [`Filter::matches`].
+    pub fn matches(&self, filter: &Filter) -> bool {
+        filter.matches(self)
+    }
+
+    /// Returns `true` when the raw command line is empty or only NUL bytes.
+    ///
+    /// Kernel threads have an empty `/proc/<pid>/cmdline`. An unreadable
+    /// command line is treated as empty.
+    pub fn cmdline_is_empty(&self) -> bool {
+        self.argv().is_none_or(|argv| argv.is_empty())
+    }
+
+    /// The command line split into its arguments, borrowed from the stored
+    /// raw command line.
+    ///
+    /// The command line is kept exactly as the kernel reports it, with NUL
+    /// separated arguments. Some programs overwrite their `argv` with a single
+    /// space separated string (e.g. `setproctitle`), so when no NUL is present
+    /// the command line is split on spaces instead.
+    pub fn argv(&self) -> Option<Vec<&str>> {
+        let cmdline = self.cmdline.as_deref()?;
+        let separator = if cmdline.contains('\0') { '\0' } else { ' ' };
+        let mut argv: Vec<&str> = cmdline.split(separator).collect();
+
+        // The kernel terminates every argument, so drop the trailing empty one.
+        while argv.last().is_some_and(|arg| arg.is_empty()) {
+            argv.pop();
+        }
+        Some(argv)
+    }
+
+    /// The executable the process runs, from the `/proc/<pid>/exe` link.
+    /// `None` for kernel threads and other users' processes when not root.
+    ///
+    /// If the file was deleted this is still the path it had, see
+    /// [`binary_deleted`](Process::binary_deleted).
+    pub fn binary_path(&self) -> Option<&Path> {
+        self.binary_path.as_deref()
+    }
+
+    /// Returns `true` if the executable was deleted or replaced after the
+    /// process started, e.g. by a package upgrade. Such processes still run
+    /// the old, possibly vulnerable, code.
+    pub fn binary_deleted(&self) -> bool {
+        self.binary_deleted
+    }
+
+    /// The command line as displayed, arguments joined by spaces, cut to at
+    /// most `max` characters with a trailing `…` if it's longer.
+    ///
+    /// Counts characters rather than bytes, so multibyte characters are
+    /// never split. Unknown command lines are shown as `-`.
+    pub fn cmdline_truncated(&self, max: usize) -> String {
+        let cmdline = Column::Cmdline.cell(self, CellFormat::default());
+        if cmdline.chars().count() <= max {
+            return cmdline;
+        }
+        match max.checked_sub(1) {
+            Some(keep) => cmdline.chars().take(keep).chain(['…']).collect(),
+            None => String::new(),
+        }
+    }
+
+    /// Returns `true` if this looks like a kernel thread: an empty command
+    /// line and `kthreadd` (PID 2) as the parent.
+    pub fn is_kernel_thread(&self) -> bool {
+        self.cmdline_is_empty() && self.ppid.is_some_and(|ppid| ppid.get() == 2)
+    }
+
+    /// Resident set size in bytes.
+    pub fn rss_bytes(&self) -> Option<u64> {
+        self.rss
+    }
+
+    /// Resident set size as a percentage of physical memory, like the
+    /// `%MEM` column of `ps`.
+    pub fn mem_percent(&self) -> Option<f64> {
+        self.mem_percent
+    }
+
+    /// Virtual memory size in bytes.
+    pub fn vsz_bytes(&self) -> Option<u64> {
+        self.vsz
+    }
+
+    /// Accumulated user plus system CPU time, what the `TIME` column of `ps`
+    /// shows. Unlike the elapsed time since start, this only grows while the
+    /// process is actually running.
+    pub fn cpu_time(&self) -> Option<Duration> {
+        self.cpu_time
+    }
+
+    /// The CPU core the process last ran on, like the `PSR` column of `ps`.
+    pub fn last_cpu(&self) -> Option<u32> {
+        self.last_cpu
+    }
+
+    /// CPU usage in percent averaged over the lifetime of the process, like
+    /// the `%CPU` column of `ps`. Can exceed 100 for multi-threaded processes.
+    pub fn cpu_percent(&self) -> Option<f64> {
+        self.cpu_percent_at(Local::now())
+    }
+
+    /// Like [`cpu_percent`](Process::cpu_percent), but averaged up to `now`.
+    pub fn cpu_percent_at(&self, now: DateTime<Local>) -> Option<f64> {
+        let elapsed = (now - self.start_time?).to_std().ok()?;
+        if elapsed.is_zero() {
+            return None;
+        }
+        Some(self.cpu_time?.as_secs_f64() / elapsed.as_secs_f64() * 100.0)
+    }
+
+    /// Compares `self` and `other` by `key`, ascending. Unknown values sort
+    /// before known ones.
+    ///
+    /// [`SortKey::Cpu`] depends on the current time, use
+    /// [`cmp_by_at`](Process::cmp_by_at) with a fixed `now` to sort a list.
+    pub fn cmp_by(&self, other: &Process, key: SortKey) -> std::cmp::Ordering {
+        self.cmp_by_at(other, key, Local::now())
+    }
+
+    /// Like [`cmp_by`](Process::cmp_by), with [`SortKey::Cpu`] averaged up to
+    /// `now` so every comparison of a sort sees the same values.
+    pub fn cmp_by_at(
+        &self,
+        other: &Process,
+        key: SortKey,
+        now: DateTime<Local>,
+    ) -> std::cmp::Ordering {
+        match key {
+            SortKey::Pid => self.pid.cmp(&other.pid),
+            SortKey::Cpu => match (self.cpu_percent_at(now), other.cpu_percent_at(now)) {
+                (Some(a), Some(b)) => a.total_cmp(&b),
+                (a, b) => a.is_some().cmp(&b.is_some()),
+            },
+            SortKey::Mem => self.rss.cmp(&other.rss),
+            SortKey::Name => self.name.cmp(&other.name),
+        }
+    }
+
+    /// Time spent on the CPU in nanoseconds, from `/proc/<pid>/schedstat`.
+    ///
+    /// Finer grained than the clock tick based `utime`/`stime` in `stat`.
+    pub fn cpu_time_ns(&self) -> Option<u64> {
+        self.cpu_time_ns
+    }
+
+    /// Time spent runnable but waiting for a CPU, in nanoseconds.
+    pub fn wait_time_ns(&self) -> Option<u64> {
+        self.wait_time_ns
+    }
+
+    /// Number of timeslices the process has run.
+    pub fn timeslices(&self) -> Option<u64> {
+        self.timeslices
+    }
+
+    /// The scheduling class like `ps -o cls`: `TS`, `FF`, `RR`, `B`, `ISO`,
+    /// `IDL` or `DLN`.
+    pub fn scheduling_class(&self) -> Option<&'static str> {
+        match self.policy? {
+            0 => Some("TS"),
+            1 => Some("FF"),
+            2 => Some("RR"),
+            3 => Some("B"),
+            4 => Some("ISO"),
+            5 => Some("IDL"),
+            6 => Some("DLN"),
+            _ => None,
         }
+    }
+
+    /// The kernel function the process is sleeping in, like `ps -o wchan`.
+    /// Together with the `D` state this points at processes stuck on I/O.
+    ///
+    /// Only read when enabled via [`ProcScanner::wchan`]. `None` for running
+    /// processes and kernel threads.
+    pub fn wchan(&self) -> Option<&str> {
+        self.wchan.as_deref()
+    }
+
+    /// Returns `true` if one of the process's file descriptors points at `path`.
+    ///
+    /// Processes whose `/proc/<pid>/fd/` we aren't allowed to read (usually
+    /// other users' when not root) report `false` rather than an error.
+    pub fn has_open_file(&self, path: &Path) -> Result<bool, PsError> {
+        // fd links are absolute and resolved, so compare against the same form.
+        let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+        let fds = match std::fs::read_dir(proc_root().join(self.pid.to_string()).join("fd")) {
+            Ok(fds) => fds,
+            Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
 
-        // Note: This may return a None, when  process ends before we get a chance to look at it, this is fine.
-        if let Some(process) = get_process(content, system_clock_tick_rate) {
-            vec_of_processs.push(process);
+        for fd in fds {
+            // Note: an fd closed while we iterate is simply skipped.
+            if std::fs::read_link(fd?.path()).is_ok_and(|target| target == path) {
+                return Ok(true);
+            }
         }
+        Ok(false)
+    }
+
+    /// The cgroup v2 path of the process, if it was requested via
+    /// [`ProcScanner::cgroup`] and the kernel exposes a unified hierarchy.
+    pub fn cgroup(&self) -> Option<&str> {
+        self.cgroup.as_deref()
+    }
+
+    /// How likely the OOM killer is to pick this process, higher is more
+    /// likely.
+    pub fn oom_score(&self) -> Option<i32> {
+        self.oom_score
+    }
+
+    /// The `oom_score_adj` bias applied to [`oom_score`](Process::oom_score),
+    /// `-1000` exempts the process from the OOM killer entirely.
+    pub fn oom_score_adj(&self) -> Option<i32> {
+        self.oom_score_adj
+    }
+
+    /// Whether `no_new_privs` is set, in which case `execve` can't grant the
+    /// process new privileges, e.g. through setuid binaries.
+    pub fn no_new_privs(&self) -> Option<bool> {
+        self.no_new_privs
+    }
+
+    /// The seccomp mode: 0 disabled, 1 strict (only `read`, `write`, `exit`
+    /// and `sigreturn`) and 2 filtered by a BPF program.
+    pub fn seccomp_mode(&self) -> Option<u8> {
+        self.seccomp_mode
+    }
+
+    /// The effective capability set as a bitmask, bit `n` being capability
+    /// number `n` (e.g. bit 21 for `CAP_SYS_ADMIN`). These are what the
+    /// kernel checks.
+    pub fn cap_effective(&self) -> Option<u64> {
+        self.cap_effective
+    }
+
+    /// The permitted capability set, the capabilities the process may make
+    /// effective.
+    pub fn cap_permitted(&self) -> Option<u64> {
+        self.cap_permitted
+    }
+
+    /// Returns whether capability number `cap` (e.g. 12 for
+    /// `CAP_NET_ADMIN`) is in the effective set.
+    ///
+    /// `None` if the set couldn't be read or `cap` isn't a valid capability
+    /// number.
+    pub fn has_capability(&self, cap: libc::c_int) -> Option<bool> {
+        let bit = u32::try_from(cap).ok().filter(|&bit| bit < u64::BITS)?;
+        Some(self.cap_effective? & (1 << bit) != 0)
+    }
+
+    /// Bytes the process caused to be read from storage. Only read when
+    /// enabled via [`ProcScanner::io`].
+    pub fn read_bytes(&self) -> Option<u64> {
+        self.read_bytes
+    }
+
+    /// Bytes the process caused to be written to storage, including dirty
+    /// pages written back later.
+    pub fn write_bytes(&self) -> Option<u64> {
+        self.write_bytes
+    }
+
+    /// UID the session of this process was started by, which stays the same
+    /// through `sudo` and setuid binaries. `None` for processes outside a
+    /// login session, e.g. daemons started at boot, or if not enabled via
+    /// [`ProcScanner::loginuid`].
+    pub fn loginuid(&self) -> Option<u32> {
+        self.loginuid
+    }
+
+    /// Username of [`loginuid`](Process::loginuid), or the numeric UID if it
+    /// has no passwd entry.
+    pub fn login_user(&self) -> Option<&str> {
+        self.login_user.as_deref()
+    }
+
+    /// Current working directory. Only read when enabled via
+    /// [`ProcScanner::cwd`], and `None` for kernel threads and for other
+    /// users' processes unless running as root.
+    pub fn cwd(&self) -> Option<&Path> {
+        self.cwd.as_deref()
+    }
+
+    /// Number of open file descriptors. Only read when enabled via
+    /// [`ProcScanner::fds`], and `None` for other users' processes unless
+    /// running as root.
+    pub fn num_fd(&self) -> Option<u32> {
+        self.num_fd
+    }
+
+    /// Soft limit on open file descriptors (`ulimit -n`), `None` if unlimited
+    /// or not enabled via [`ProcScanner::fds`].
+    pub fn fd_limit(&self) -> Option<u64> {
+        self.fd_limit
+    }
+
+    /// Open file descriptors as a fraction of [`fd_limit`](Process::fd_limit),
+    /// e.g. to warn about processes close to running out.
+    pub fn fd_usage_ratio(&self) -> Option<f64> {
+        let fd_limit = self.fd_limit.filter(|&fd_limit| fd_limit > 0)?;
+        Some(f64::from(self.num_fd?) / fd_limit as f64)
+    }
+
+    /// Real, effective, saved and filesystem user IDs, in that order.
+    pub fn uid(&self) -> Option<[u32; 4]> {
+        self.uid
+    }
+
+    /// Real, effective, saved and filesystem group IDs, in that order.
+    pub fn gid(&self) -> Option<[u32; 4]> {
+        self.gid
+    }
+
+    /// Returns `true` if the process runs with a different effective than
+    /// real user ID, e.g. `passwd` or `sudo`. Unknown UIDs conservatively
+    /// count as not setuid.
+    pub fn is_setuid(&self) -> bool {
+        self.uid
+            .is_some_and(|[real, effective, ..]| real != effective)
+    }
+
+    /// Times the process gave up the CPU itself, e.g. to wait for I/O or a
+    /// lock. A high count points at a process that is mostly waiting.
+    pub fn voluntary_ctx_switches(&self) -> Option<u64> {
+        self.voluntary_ctx_switches
+    }
+
+    /// Times the scheduler took the CPU away from the process, e.g. because
+    /// its timeslice ran out. A high count points at CPU contention.
+    pub fn nonvoluntary_ctx_switches(&self) -> Option<u64> {
+        self.nonvoluntary_ctx_switches
+    }
+
+    /// Page faults that didn't need disk I/O, e.g. the first touch of newly
+    /// allocated memory.
+    pub fn minor_faults(&self) -> Option<u64> {
+        self.minor_faults
+    }
+
+    /// Page faults that had to load the page from disk. A steadily growing
+    /// count usually means the process is short on memory.
+    pub fn major_faults(&self) -> Option<u64> {
+        self.major_faults
+    }
+
+    /// Start address of the program's text segment, e.g. to tell whether an
+    /// instruction address lies in the main binary or a library.
+    ///
+    /// `None` for kernel threads and processes we may not ptrace, for which
+    /// the kernel hides the address.
+    pub fn start_code(&self) -> Option<u64> {
+        self.start_code
+    }
+
+    /// End address of the program's text segment, see
+    /// [`start_code`](Process::start_code).
+    pub fn end_code(&self) -> Option<u64> {
+        self.end_code
+    }
+
+    /// Returns `true` if both describe the same process, e.g. the same
+    /// process seen in two scans.
+    ///
+    /// PIDs get reused, so besides the PID the start times have to match.
+    /// They are derived from clock ticks and the uptime, so they can differ
+    /// by up to a second between scans. If either start time is unknown only
+    /// the PIDs are compared.
+    pub fn is_same_process_as(&self, other: &Process) -> bool {
+        self.pid == other.pid
+            && match (self.start_time, other.start_time) {
+                (Some(a), Some(b)) => (a - b).abs() <= chrono::TimeDelta::seconds(1),
+                _ => true,
+            }
+    }
+
+    /// Re-reads the process from `/proc/<pid>` in place, e.g. to track one
+    /// process over time without scanning all of them.
+    ///
+    /// Reads from `scanner`'s root with its optional fields, usually the
+    /// scanner that read the process in the first place. Returns `Ok(false)`
+    /// and leaves `self` unchanged if the process has exited, including when
+    /// its PID now belongs to another process. Non-fatal errors are dropped,
+    /// use [`refresh_with_errors`](Process::refresh_with_errors) to get them.
+    pub fn refresh(&mut self, scanner: &ProcScanner) -> Result<bool, PsError> {
+        self.refresh_with_errors(scanner)
+            .map(|(refreshed, _)| refreshed)
+    }
+
+    /// Like [`refresh`](Process::refresh), but also returns the non-fatal
+    /// errors hit while reading, like [`ProcScanner::scan_with_errors`].
+    pub fn refresh_with_errors(
+        &mut self,
+        scanner: &ProcScanner,
+    ) -> Result<(bool, Vec<PsError>), PsError> {
+        let root = scanner.root.clone().unwrap_or_else(proc_root);
+        let mut path = root.join(self.pid.to_string());
+        if let Some(tid) = self.tid {
+            path = path.join("task").join(tid.to_string());
+        }
+        match std::fs::metadata(&path) {
+            Ok(metadata) if metadata.is_dir() => {}
+            Ok(_) => return Ok((false, vec![])),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok((false, vec![])),
+            Err(e) => return Err(e.into()),
+        }
+
+        let mut warnings = vec![];
+        let context = ScanContext::new(&root, &mut warnings)?;
+        let mut fresh = read_process(self.pid, &path, &context, scanner, &mut warnings);
+        if !fresh.is_same_process_as(self) {
+            return Ok((false, warnings));
+        }
+
+        fresh.tid = self.tid;
+        if let (Some(before), Some(after), Some(read_at), Some(now)) =
+            (self.cpu_time, fresh.cpu_time, self.read_at, fresh.read_at)
+        {
+            let elapsed = now.duration_since(read_at);
+            if !elapsed.is_zero() {
+                fresh.recent_cpu_percent = Some(
+                    after.saturating_sub(before).as_secs_f64() / elapsed.as_secs_f64() * 100.0,
+                );
+            }
+        }
+        *self = fresh;
+        Ok((true, warnings))
+    }
+
+    /// CPU usage in percent between the last two reads, like the `%CPU`
+    /// column of `top`. `None` until [`refresh`](Process::refresh) has been
+    /// called, unlike [`cpu_percent`](Process::cpu_percent) which averages
+    /// over the whole lifetime.
+    pub fn recent_cpu_percent(&self) -> Option<f64> {
+        self.recent_cpu_percent
+    }
+
+    /// Sends `signal` (e.g. `libc::SIGTERM`) to the process.
+    ///
+    /// Returns [`PsError::ProcessGone`] if the process has exited since it
+    /// was read and [`PsError::PermissionDenied`] if it belongs to another
+    /// user. Note that the PID may have been reused by an unrelated process
+    /// in the meantime.
+    pub fn send_signal(&self, signal: libc::c_int) -> Result<(), PsError> {
+        // PIDs are non-zero and capped at pid_max, so this never signals a
+        // process group and always fits in a pid_t.
+        if unsafe { libc::kill(self.pid.get() as libc::pid_t, signal) } == 0 {
+            return Ok(());
+        }
+
+        match std::io::Error::last_os_error().raw_os_error() {
+            Some(libc::ESRCH) => Err(PsError::ProcessGone(self.pid)),
+            Some(libc::EPERM) => Err(PsError::PermissionDenied {
+                pid: self.pid,
+                file: None,
+            }),
+            err_num => Err(PsError::FailedToSendSignal(err_num.unwrap_or_default())),
+        }
+    }
+
+    /// Sends `SIGKILL`, which the process can't catch or ignore.
+    ///
+    /// It gets no chance to clean up, so this is a last resort. Prefer
+    /// `send_signal(libc::SIGTERM)` for a graceful shutdown and only kill
+    /// processes that don't exit after that. Errors are the same as for
+    /// [`send_signal`](Process::send_signal).
+    pub fn kill(&self) -> Result<(), PsError> {
+        self.send_signal(libc::SIGKILL)
+    }
+}
+
+/// Reads a single `/proc/<pid>` entry with the default [`ProcScanner`] options.
+///
+/// Entries that aren't processes, such as `/proc/bus`, give
+/// [`PsError::NotAProcess`]. Non-fatal errors are dropped like in
+/// [`ProcScanner::scan`].
+impl TryFrom<DirEntry> for Process {
+    type Error = PsError;
+
+    fn try_from(dir_ent: DirEntry) -> Result<Self, Self::Error> {
+        // The entry lives directly inside the proc mount.
+        let root = dir_ent
+            .path()
+            .parent()
+            .map(Path::to_path_buf)
+            .unwrap_or_else(proc_root);
+        let mut warnings = vec![];
+        let context = ScanContext::new(&root, &mut warnings)?;
+        get_process(dir_ent, &context, &ProcScanner::new(), &mut warnings)
+    }
+}
+
+impl Process {
+    /// A process with every field but the PID `None`, for filling in only
+    /// some fields. Use [`ProcessBuilder`] outside the crate.
+    ///
+    /// There's no `Default`, since PID 0 would make
+    /// [`send_signal`](Process::send_signal) signal the caller's own process
+    /// group.
+    fn empty(pid: Pid) -> Self {
+        Process {
+            pid,
+            tid: None,
+            ppid: None,
+            pgid: None,
+            sid: None,
+            name: None,
+            cmdline: None,
+            binary_path: None,
+            binary_deleted: false,
+            owner: None,
+            start_time: None,
+            state: None,
+            rss: None,
+            mem_percent: None,
+            vsz: None,
+            cpu_time: None,
+            cpu_time_ns: None,
+            wait_time_ns: None,
+            timeslices: None,
+            policy: None,
+            last_cpu: None,
+            wchan: None,
+            cgroup: None,
+            oom_score: None,
+            oom_score_adj: None,
+            no_new_privs: None,
+            seccomp_mode: None,
+            cap_effective: None,
+            cap_permitted: None,
+            read_bytes: None,
+            write_bytes: None,
+            loginuid: None,
+            login_user: None,
+            cwd: None,
+            num_fd: None,
+            fd_limit: None,
+            uid: None,
+            gid: None,
+            voluntary_ctx_switches: None,
+            nonvoluntary_ctx_switches: None,
+            minor_faults: None,
+            major_faults: None,
+            start_code: None,
+            end_code: None,
+            read_at: None,
+            read_time: None,
+            recent_cpu_percent: None,
+        }
+    }
+}
+
+/// Builds a [`Process`] with chosen fields for tests and mocks.
+///
+/// Fields that aren't set stay `None`.
+#[derive(Debug, Clone)]
+pub struct ProcessBuilder {
+    process: Process,
+}
+
+impl ProcessBuilder {
+    pub fn new(pid: Pid) -> Self {
+        ProcessBuilder {
+            process: Process::empty(pid),
+        }
+    }
+
+    /// The raw command line, arguments separated by NUL like in
+    /// `/proc/<pid>/cmdline` or by spaces.
+    pub fn cmdline(mut self, cmdline: impl Into<String>) -> Self {
+        self.process.cmdline = Some(cmdline.into());
+        self
+    }
+
+    pub fn owner(mut self, owner: impl Into<String>) -> Self {
+        self.process.owner = Some(owner.into());
+        self
+    }
+
+    pub fn state(mut self, state: ProcessState) -> Self {
+        self.process.state = Some(state);
+        self
+    }
+
+    pub fn binary_path(mut self, binary_path: impl Into<PathBuf>) -> Self {
+        self.process.binary_path = Some(binary_path.into());
+        self
+    }
+
+    pub fn start_time(mut self, start_time: DateTime<Local>) -> Self {
+        self.process.start_time = Some(start_time);
+        self
+    }
+
+    pub fn build(self) -> Process {
+        self.process
+    }
+}
+
+/// Columns and widths of the fixed-width [`Process`] rows.
+const ROW_COLUMNS: [(Column, usize); 11] = [
+    (Column::Pid, 10),
+    (Column::Owner, 15),
+    (Column::Cmdline, 15),
+    (Column::BinaryPath, 30),
+    (Column::StartTime, 20),
+    (Column::State, 15),
+    (Column::Rss, 10),
+    (Column::Vsz, 10),
+    (Column::Time, 12),
+    (Column::Psr, 4),
+    (Column::Wchan, 20),
+];
+
+impl Process {
+    /// The header line matching the fixed-width rows of the [`Display`](fmt::Display)
+    /// implementation, to print once above them. Like the rows it has no
+    /// line break.
+    pub fn header() -> String {
+        ROW_COLUMNS
+            .iter()
+            .map(|&(column, width)| format!("{:<width$}", column.header()))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// A short one-line summary for logs, e.g. `1234 alice R firefox (120.0M)`.
+    ///
+    /// Unknown values are `-`, except memory which is left out entirely.
+    pub fn format_compact(&self) -> String {
+        let mut compact = format!(
+            "{} {} {} {}",
+            self.pid,
+            self.owner.as_deref().unwrap_or("-"),
+            self.state.map_or('-', ProcessState::as_char),
+            self.name
+                .as_deref()
+                .map_or_else(|| "-".to_owned(), escape_control),
+        );
+        if let Some(rss) = self.rss {
+            compact.push_str(&format!(" ({})", format_bytes(rss)));
+        }
+        compact
+    }
+}
+
+/// Processes compare by PID, and by TID for the rows of
+/// [`ProcScanner::threads`], nothing else. Two scans of the same PID are
+/// equal even if the PID was reused in between, see
+/// [`Process::is_same_process_as`] for that.
+impl PartialEq for Process {
+    fn eq(&self, other: &Self) -> bool {
+        (self.pid, self.tid) == (other.pid, other.tid)
+    }
+}
+
+impl Eq for Process {}
+
+/// Orders by PID, then by TID with the process itself before its threads,
+/// consistent with [`PartialEq`]. `sort` gives the order of a scan.
+impl Ord for Process {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.pid, self.tid).cmp(&(other.pid, other.tid))
+    }
+}
+
+impl PartialOrd for Process {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Pretty-print implementation for `Process` as a single fixed-width row
+/// without a line break, see [`Process::header`] for the matching header.
+///
+/// `{:+}` prints the memory columns as exact byte counts instead of e.g. `12.3M`.
+impl fmt::Display for Process {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let format = CellFormat {
+            raw_bytes: f.sign_plus(),
+            ..CellFormat::default()
+        };
+
+        for (i, &(column, width)) in ROW_COLUMNS.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            let cell = match column {
+                // Keep the row aligned, the full command line is in `argv`.
+                Column::Cmdline => self.cmdline_truncated(width),
+                _ => column.cell(self, format),
+            };
+            write!(f, "{cell:<width$}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A column of the process listing, see [`ProcessTable::columns`].
+///
+/// Parses from its [`name`](Column::name), e.g. `"rss"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Column {
+    Pid,
+    Tid,
+    Ppid,
+    Pgid,
+    Sid,
+    Owner,
+    Cmdline,
+    BinaryPath,
+    StartTime,
+    State,
+    Rss,
+    MemPercent,
+    Vsz,
+    Time,
+    /// [`Process::cpu_percent`].
+    CpuPercent,
+    Psr,
+    Wchan,
+    OomScore,
+    OomScoreAdj,
+    NumFd,
+    Cwd,
+}
+
+impl Column {
+    /// Every column that can be selected with [`ProcessTable::columns`], in
+    /// the default table order. Like `ps`, the command line goes last so a
+    /// long one doesn't push the others out of alignment.
+    pub const FIELDS: [Column; 21] = [
+        Column::Pid,
+        Column::Tid,
+        Column::Ppid,
+        Column::Pgid,
+        Column::Sid,
+        Column::Owner,
+        Column::BinaryPath,
+        Column::StartTime,
+        Column::State,
+        Column::Rss,
+        Column::MemPercent,
+        Column::Vsz,
+        Column::Time,
+        Column::CpuPercent,
+        Column::Psr,
+        Column::Wchan,
+        Column::OomScore,
+        Column::OomScoreAdj,
+        Column::NumFd,
+        Column::Cwd,
+        Column::Cmdline,
+    ];
+
+    /// Short lowercase name to select the column by, like the keys of `ps -o`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Column::Pid => "pid",
+            Column::Tid => "tid",
+            Column::Ppid => "ppid",
+            Column::Pgid => "pgid",
+            Column::Sid => "sid",
+            Column::Owner => "owner",
+            Column::Cmdline => "cmdline",
+            Column::BinaryPath => "exe",
+            Column::StartTime => "start",
+            Column::State => "state",
+            Column::Rss => "rss",
+            Column::MemPercent => "mem",
+            Column::Vsz => "vsz",
+            Column::Time => "time",
+            Column::CpuPercent => "cpu",
+            Column::Psr => "psr",
+            Column::Wchan => "wchan",
+            Column::OomScore => "oom",
+            Column::OomScoreAdj => "oom_adj",
+            Column::NumFd => "fds",
+            Column::Cwd => "cwd",
+        }
+    }
+
+    fn header(self) -> &'static str {
+        match self {
+            Column::Pid => "PID",
+            Column::Tid => "TID",
+            Column::Ppid => "PPID",
+            Column::Pgid => "PGID",
+            Column::Sid => "SID",
+            Column::Owner => "Owner",
+            Column::Cmdline => "Cmdline",
+            Column::BinaryPath => "Binary Path",
+            Column::StartTime => "Start Time",
+            Column::State => "State",
+            Column::Rss => "RSS",
+            Column::MemPercent => "%MEM",
+            Column::Vsz => "VSZ",
+            Column::Time => "TIME",
+            Column::CpuPercent => "%CPU",
+            Column::Psr => "PSR",
+            Column::OomScore => "OOM",
+            Column::OomScoreAdj => "OOM_ADJ",
+            Column::NumFd => "FDS",
+            Column::Cwd => "CWD",
+            Column::Wchan => "WCHAN",
+        }
+    }
+
+    /// Columns for opt-in fields, left out of a [`ProcessTable`] unless a
+    /// process has a value for them.
+    fn is_optional(self) -> bool {
+        matches!(self, Column::Tid | Column::NumFd | Column::Cwd)
+    }
+
+    /// Formats this column for `process`, `None` if the value is unknown.
+    fn value(self, process: &Process, format: CellFormat) -> Option<String> {
+        let memory = |bytes: Option<u64>| {
+            bytes.map(|bytes| {
+                if format.raw_bytes {
+                    bytes.to_string()
+                } else {
+                    format_bytes(bytes)
+                }
+            })
+        };
+
+        match self {
+            Column::Pid => Some(process.pid.to_string()),
+            Column::Tid => process.tid.map(|tid| tid.to_string()),
+            Column::Ppid => process.ppid.map(|ppid| ppid.to_string()),
+            Column::Pgid => process.pgid.map(|pgid| pgid.to_string()),
+            Column::Sid => process.sid.map(|sid| sid.to_string()),
+            Column::Owner => process.owner.as_deref().map(escape_control),
+            Column::Cmdline => process.argv().map(|argv| escape_control(&argv.join(" "))),
+            Column::BinaryPath => process.binary_path.as_deref().map(|path| {
+                let path = escape_control(&path.to_string_lossy());
+                if process.binary_deleted {
+                    format!("{path} (deleted)")
+                } else {
+                    path
+                }
+            }),
+            // Format the datetime as a normal readable string.
+            Column::StartTime if format.utc => process
+                .start_time_utc()
+                .map(|date_time| date_time.format("%Y-%m-%dT%H:%M:%SZ").to_string()),
+            Column::StartTime => process
+                .start_time
+                .map(|date_time| date_time.format("%Y-%m-%d %H:%M:%S").to_string()),
+            Column::State => process.state.map(|state| state.to_string()),
+            Column::Rss => memory(process.rss),
+            Column::MemPercent => process.mem_percent.map(|percent| format!("{percent:.1}")),
+            Column::Vsz => memory(process.vsz),
+            Column::Time => process.cpu_time.map(format_cpu_time),
+            Column::CpuPercent => process.cpu_percent().map(|percent| format!("{percent:.1}")),
+            Column::Psr => process.last_cpu.map(|cpu| cpu.to_string()),
+            Column::Wchan => process.wchan.as_deref().map(escape_control),
+            Column::OomScore => process.oom_score.map(|score| score.to_string()),
+            Column::OomScoreAdj => process.oom_score_adj.map(|adj| adj.to_string()),
+            Column::NumFd => process.num_fd.map(|num_fd| num_fd.to_string()),
+            Column::Cwd => process
+                .cwd
+                .as_deref()
+                .map(|cwd| escape_control(&cwd.to_string_lossy())),
+        }
+    }
+
+    /// Like [`value`](Column::value), with a placeholder for unknown values.
+    fn cell(self, process: &Process, format: CellFormat) -> String {
+        self.value(process, format).unwrap_or_else(|| {
+            match self {
+                Column::BinaryPath => "",
+                Column::StartTime => "unknown",
+                _ => "-",
+            }
+            .to_owned()
+        })
+    }
+}
+
+/// Replaces control characters with a visible `\xNN` escape.
+///
+/// Command lines and paths are chosen by the process. Printed as-is, an
+/// argument like `\x1b[2J` would clear the terminal of whoever lists it.
+fn escape_control(s: &str) -> String {
+    let mut escaped = String::with_capacity(s.len());
+    for c in s.chars() {
+        match c {
+            // Covers C0, DEL and the C1 range, all below U+0100.
+            c if c.is_control() => escaped.push_str(&format!("\\x{:02x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl FromStr for Column {
+    type Err = PsError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Column::FIELDS
+            .into_iter()
+            .find(|column| column.name() == name)
+            .ok_or_else(|| PsError::UnknownField(name.to_owned()))
+    }
+}
+
+/// How [`Column`] values are formatted.
+#[derive(Debug, Clone, Copy, Default)]
+struct CellFormat {
+    /// Memory as exact byte counts instead of e.g. `12.3M`.
+    raw_bytes: bool,
+    /// Start times in UTC instead of the local timezone.
+    utc: bool,
+}
+
+/// Columns that give up space first when a [`ProcessTable`] doesn't fit.
+const SHRINKABLE_COLUMNS: [Column; 2] = [Column::Cmdline, Column::BinaryPath];
+
+/// Shortens `s` to at most `width` characters, cutting at the last space
+/// that fits and marking the cut with `…`.
+fn truncate_at_word(s: &str, width: usize) -> String {
+    if s.chars().count() <= width {
+        return s.to_owned();
+    }
+
+    // Leave room for the ellipsis.
+    let cut: String = s.chars().take(width.saturating_sub(1)).collect();
+    let cut = match cut.rfind(' ') {
+        Some(space) if space > 0 => &cut[..space],
+        _ => &cut,
+    };
+    format!("{cut}…")
+}
+
+/// An aligned table of processes with column widths measured from the data.
+///
+/// Unlike the fixed widths of the per-process [`Display`](fmt::Display),
+/// each column is as wide as its widest cell, so this needs the whole set
+/// up front. If a [`max_width`](ProcessTable::max_width) is set and a row
+/// doesn't fit, the command line and then the binary path are shortened.
+#[derive(Debug, Clone, Copy)]
+pub struct ProcessTable<'a> {
+    processes: &'a [Process],
+    format: CellFormat,
+    max_width: Option<usize>,
+    job_columns: bool,
+    oom_columns: bool,
+    header: bool,
+    /// Exactly these columns, instead of picking them by the options.
+    columns: Option<&'a [Column]>,
+}
+
+impl<'a> ProcessTable<'a> {
+    pub fn new(processes: &'a [Process]) -> Self {
+        ProcessTable {
+            processes,
+            format: CellFormat::default(),
+            max_width: None,
+            job_columns: false,
+            oom_columns: false,
+            header: true,
+            columns: None,
+        }
+    }
+
+    /// Show exactly `columns` in this order, like `ps -o`. Overrides
+    /// [`job_columns`](ProcessTable::job_columns),
+    /// [`oom_columns`](ProcessTable::oom_columns) and shows opt-in columns
+    /// even if no process has a value for them.
+    pub fn columns(mut self, columns: &'a [Column]) -> Self {
+        self.columns = Some(columns);
+        self
+    }
+
+    /// Print memory columns as exact byte counts instead of e.g. `12.3M`.
+    pub fn raw_bytes(mut self, raw_bytes: bool) -> Self {
+        self.format.raw_bytes = raw_bytes;
+        self
+    }
+
+    /// Print start times in UTC instead of the local timezone.
+    pub fn utc(mut self, utc: bool) -> Self {
+        self.format.utc = utc;
+        self
+    }
+
+    /// Print the header line above the rows, on by default. Also applies to
+    /// [`write_csv`](ProcessTable::write_csv).
+    pub fn header(mut self, enabled: bool) -> Self {
+        self.header = enabled;
+        self
+    }
+
+    /// Add the process group and session ID columns, like `ps -j`.
+    pub fn job_columns(mut self, enabled: bool) -> Self {
+        self.job_columns = enabled;
+        self
+    }
+
+    /// Add the [`oom_score`](Process::oom_score) and
+    /// [`oom_score_adj`](Process::oom_score_adj) columns.
+    pub fn oom_columns(mut self, enabled: bool) -> Self {
+        self.oom_columns = enabled;
+        self
+    }
+
+    /// Limit rows to `width` characters, typically the terminal width.
+    pub fn max_width(mut self, width: usize) -> Self {
+        self.max_width = Some(width);
+        self
+    }
+
+    /// Writes the table to `w`, same as formatting it with `{}`.
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        write!(w, "{self}")
+    }
+
+    /// Writes the processes to `w` as newline delimited JSON, one
+    /// [`Process::to_json`] object per line. Only the [`utc`](ProcessTable::utc)
+    /// option applies, there's no header and memory is always in bytes.
+    pub fn write_ndjson<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        for process in self.processes {
+            writeln!(w, "{}", process.json(self.format))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the table to `w` as CSV, with a header line unless
+    /// [`header`](ProcessTable::header) is off. Unknown values are empty,
+    /// memory is always in bytes and [`max_width`](ProcessTable::max_width)
+    /// doesn't apply.
+    pub fn write_csv<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let format = self.machine_format();
+        let columns = self.selected_columns();
+        if self.header {
+            let headers: Vec<&str> = columns.iter().map(|column| column.header()).collect();
+            writeln!(w, "{}", headers.join(","))?;
+        }
+        for process in self.processes {
+            let row: Vec<String> = columns
+                .iter()
+                .map(|column| csv_field(&column.value(process, format).unwrap_or_default()))
+                .collect();
+            writeln!(w, "{}", row.join(","))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the table to `w` with the fields of each row joined by
+    /// `delimiter` and no padding, e.g. tab-separated for `cut`. Unknown
+    /// values are empty, memory is always in bytes and nothing is quoted, so
+    /// a delimiter inside a value (usually the command line) isn't escaped.
+    /// Use [`write_csv`](ProcessTable::write_csv) for comma-separated output.
+    pub fn write_delimited<W: Write>(&self, w: &mut W, delimiter: char) -> io::Result<()> {
+        let format = self.machine_format();
+        let delimiter = delimiter.to_string();
+        let columns = self.selected_columns();
+        if self.header {
+            let headers: Vec<&str> = columns.iter().map(|column| column.header()).collect();
+            writeln!(w, "{}", headers.join(&delimiter))?;
+        }
+        for process in self.processes {
+            let row: Vec<String> = columns
+                .iter()
+                .map(|column| column.value(process, format).unwrap_or_default())
+                .collect();
+            writeln!(w, "{}", row.join(&delimiter))?;
+        }
+        Ok(())
+    }
+
+    /// Writes the processes to `w` as a JSON array of [`Process::to_json`]
+    /// objects, one per line. Only the [`utc`](ProcessTable::utc) option
+    /// applies.
+    pub fn write_json<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        writeln!(w, "[")?;
+        for (i, process) in self.processes.iter().enumerate() {
+            let separator = if i + 1 < self.processes.len() {
+                ","
+            } else {
+                ""
+            };
+            writeln!(w, "{}{separator}", process.json(self.format))?;
+        }
+        writeln!(w, "]")
+    }
+
+    /// The columns to print, optional ones only if they're enabled or have
+    /// a value.
+    /// The cell format for output meant for other programs, with exact byte
+    /// counts instead of e.g. `12.3M`.
+    fn machine_format(&self) -> CellFormat {
+        CellFormat {
+            raw_bytes: true,
+            ..self.format
+        }
+    }
+
+    fn selected_columns(&self) -> Vec<Column> {
+        if let Some(columns) = self.columns {
+            return columns.to_vec();
+        }
+        Column::FIELDS
+            .into_iter()
+            .filter(|column| match column {
+                // Only shown when selected.
+                Column::Ppid | Column::CpuPercent => false,
+                Column::Pgid | Column::Sid => self.job_columns,
+                Column::OomScore | Column::OomScoreAdj => self.oom_columns,
+                _ if column.is_optional() => self
+                    .processes
+                    .iter()
+                    .any(|process| column.value(process, self.format).is_some()),
+                _ => true,
+            })
+            .collect()
+    }
+
+    /// Column widths fitting every cell, shrunk to `max_width` if set.
+    fn widths(&self, columns: &[Column], cells: &[Vec<String>]) -> Vec<usize> {
+        let mut widths: Vec<usize> = columns
+            .iter()
+            .map(|column| column.header().chars().count())
+            .collect();
+        for row in cells {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                *width = (*width).max(cell.chars().count());
+            }
+        }
+
+        let total = (widths.iter().sum::<usize>() + widths.len()).saturating_sub(1);
+        if let Some(max_width) = self.max_width
+            && total > max_width
+        {
+            let mut excess = total - max_width;
+            for column in SHRINKABLE_COLUMNS {
+                let Some(i) = columns.iter().position(|c| *c == column) else {
+                    continue;
+                };
+                // Never narrower than the header.
+                let shrink = excess.min(widths[i].saturating_sub(column.header().len()));
+                widths[i] -= shrink;
+                excess -= shrink;
+            }
+        }
+        widths
+    }
+}
+
+impl fmt::Display for ProcessTable<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let columns = self.selected_columns();
+        let cells: Vec<Vec<String>> = self
+            .processes
+            .iter()
+            .map(|process| {
+                columns
+                    .iter()
+                    .map(|column| column.cell(process, self.format))
+                    .collect()
+            })
+            .collect();
+        let widths = self.widths(&columns, &cells);
+
+        let headers = columns.iter().map(|column| column.header().to_owned());
+        let mut write_row = |row: &mut dyn Iterator<Item = String>| {
+            let line = row
+                .zip(&widths)
+                .map(|(cell, &width)| format!("{:<width$}", truncate_at_word(&cell, width)))
+                .collect::<Vec<_>>()
+                .join(" ");
+            // The last column isn't padded.
+            writeln!(f, "{}", line.trim_end())
+        };
+
+        if self.header {
+            write_row(&mut headers.into_iter())?;
+        }
+        for row in cells {
+            write_row(&mut row.into_iter())?;
+        }
+        Ok(())
+    }
+}
+
+/// Quotes a CSV field if it contains a separator, quote or line break.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_owned()
+    }
+}
+
+/// Quotes `s` as a JSON string.
+fn json_string(s: &str) -> String {
+    let mut quoted = String::with_capacity(s.len() + 2);
+    quoted.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => quoted.push_str("\\\""),
+            '\\' => quoted.push_str("\\\\"),
+            '\n' => quoted.push_str("\\n"),
+            '\r' => quoted.push_str("\\r"),
+            '\t' => quoted.push_str("\\t"),
+            c if c.is_control() => quoted.push_str(&format!("\\u{:04x}", c as u32)),
+            c => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
+impl Process {
+    /// Formats the process as a single line JSON object.
+    ///
+    /// Memory is in bytes, CPU time in seconds and the start time in RFC 3339
+    /// with the local UTC offset. Unknown values are `null`.
+    pub fn to_json(&self) -> String {
+        self.json(CellFormat::default())
+    }
+
+    fn json(&self, format: CellFormat) -> String {
+        fn or_null<T>(value: Option<T>, to_json: impl FnOnce(T) -> String) -> String {
+            value.map_or_else(|| "null".to_owned(), to_json)
+        }
+        let number = |value: Option<u64>| or_null(value, |value| value.to_string());
+        let string = |value: Option<&str>| or_null(value, json_string);
+        let pid = |pid: Option<Pid>| or_null(pid, |pid| pid.to_string());
+
+        let start_time = self.start_time.map(|date_time| {
+            if format.utc {
+                date_time.with_timezone(&Utc).to_rfc3339()
+            } else {
+                date_time.to_rfc3339()
+            }
+        });
+        let argv = self.argv().map(|argv| {
+            let args: Vec<String> = argv.into_iter().map(json_string).collect();
+            format!("[{}]", args.join(","))
+        });
+        let fields = [
+            ("pid", self.pid.to_string()),
+            ("tid", pid(self.tid)),
+            ("ppid", pid(self.ppid)),
+            ("pgid", pid(self.pgid)),
+            ("sid", pid(self.sid)),
+            ("name", string(self.name.as_deref())),
+            ("argv", or_null(argv, |argv| argv)),
+            (
+                "binary_path",
+                string(self.binary_path.as_deref().and_then(Path::to_str)),
+            ),
+            ("binary_deleted", self.binary_deleted.to_string()),
+            ("owner", string(self.owner.as_deref())),
+            ("start_time", string(start_time.as_deref())),
+            (
+                "state",
+                string(
+                    self.state
+                        .map(|state| state.as_char().to_string())
+                        .as_deref(),
+                ),
+            ),
+            ("rss", number(self.rss)),
+            (
+                "mem_percent",
+                or_null(self.mem_percent, |percent| percent.to_string()),
+            ),
+            ("vsz", number(self.vsz)),
+            (
+                "cpu_time",
+                or_null(self.cpu_time, |cpu_time| cpu_time.as_secs_f64().to_string()),
+            ),
+            ("last_cpu", number(self.last_cpu.map(u64::from))),
+            ("wchan", string(self.wchan.as_deref())),
+            ("cgroup", string(self.cgroup.as_deref())),
+            (
+                "oom_score",
+                or_null(self.oom_score, |score| score.to_string()),
+            ),
+            (
+                "oom_score_adj",
+                or_null(self.oom_score_adj, |adj| adj.to_string()),
+            ),
+            ("num_fd", number(self.num_fd.map(u64::from))),
+            ("fd_limit", number(self.fd_limit)),
+            ("cwd", string(self.cwd.as_deref().and_then(Path::to_str))),
+        ];
+
+        let fields: Vec<String> = fields
+            .iter()
+            .map(|(key, value)| format!("\"{key}\":{value}"))
+            .collect();
+        format!("{{{}}}", fields.join(","))
+    }
+}
+
+/// Rows of processes using at least this much memory or CPU are highlighted.
+#[cfg(feature = "color")]
+const HIGH_MEMORY_BYTES: u64 = 1 << 30;
+#[cfg(feature = "color")]
+const HIGH_CPU_PERCENT: f64 = 50.0;
+
+impl<'a> ProcessListDisplay<'a> {
+    pub fn new(table: ProcessTable<'a>) -> Self {
+        ProcessListDisplay {
+            table,
+            color: false,
+        }
+    }
+
+    /// Highlight rows with ANSI escape codes, off by default.
+    pub fn color(mut self, enabled: bool) -> Self {
+        self.color = enabled;
+        self
+    }
+
+    /// Writes the table to `w`, colored if enabled.
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        #[cfg(feature = "color")]
+        if self.color {
+            use termcolor::{Ansi, ColorSpec, WriteColor};
+
+            let table = self.table.to_string();
+            let mut lines = table.lines();
+            let mut w = Ansi::new(w);
+            if self.table.header
+                && let Some(header) = lines.next()
+            {
+                writeln!(w, "{header}")?;
+            }
+            for (process, row) in self.table.processes.iter().zip(lines) {
+                match row_color(process) {
+                    Some(color) => {
+                        w.set_color(ColorSpec::new().set_fg(Some(color)))?;
+                        write!(w, "{row}")?;
+                        // Reset before the line break so the color doesn't bleed.
+                        w.reset()?;
+                        writeln!(w)?;
+                    }
+                    None => writeln!(w, "{row}")?,
+                }
+            }
+            return Ok(());
+        }
+
+        self.table.write_to(w)
+    }
+}
+
+/// Highlight color of a row, zombies take precedence over resource usage.
+#[cfg(feature = "color")]
+fn row_color(process: &Process) -> Option<termcolor::Color> {
+    use termcolor::Color;
+
+    let busy = process.rss.is_some_and(|rss| rss >= HIGH_MEMORY_BYTES)
+        || process
+            .cpu_percent()
+            .is_some_and(|cpu| cpu >= HIGH_CPU_PERCENT);
+    match process.state {
+        Some(ProcessState::Zombie) => Some(Color::Red),
+        _ if busy => Some(Color::Yellow),
+        Some(ProcessState::Running) => Some(Color::Green),
+        _ => None,
+    }
+}
+
+/// Writes `procs` as a [`ProcessTable`] with the default options to `w`.
+///
+/// Pass `io::stdout().lock()` to print it, or a `Vec<u8>` to capture it.
+pub fn write_table<W: Write>(procs: &[Process], w: &mut W) -> io::Result<()> {
+    ProcessTable::new(procs).write_to(w)
+}
+
+/// Writes `procs` to `w` as newline delimited JSON, see
+/// [`ProcessTable::write_ndjson`].
+pub fn write_ndjson<W: Write>(procs: &[Process], w: &mut W) -> io::Result<()> {
+    ProcessTable::new(procs).write_ndjson(w)
+}
+
+/// Parses both values of `/proc/uptime` in seconds: the time since boot and
+/// the idle time summed over all CPUs. Only the uptime is required, the idle
+/// time is `None` if it's missing or malformed.
+fn parse_uptime(uptime: &str) -> Result<(f64, Option<f64>), PsError> {
+    let mut values = uptime.split_whitespace();
+    let uptime_seconds = values
+        .next()
+        // ok_or checks some (if there is a value) if not errors.
+        .ok_or(PsError::FailedToGetUptimeFromStat)?
+        // tries to turn "48267.42" into f64.
+        .parse()?;
+    let idle_seconds = values.next().and_then(|idle| idle.parse().ok());
+    Ok((uptime_seconds, idle_seconds))
+}
+
+/// Returns the system uptime in seconds, the first value in `/proc/uptime`.
+fn get_uptime(uptime_path: &Path) -> Result<f64, PsError> {
+    let uptime_res = std::fs::read_to_string(uptime_path)?;
+    let (uptime_seconds, _) = parse_uptime(&uptime_res)?;
+    Ok(uptime_seconds)
+}
+
+/// Returns the time since boot and the idle time from `/proc/uptime`.
+///
+/// The idle time is summed over all CPUs, so on an idle machine it grows
+/// faster than the uptime.
+pub fn system_uptime() -> Result<(Duration, Duration), PsError> {
+    let uptime = std::fs::read_to_string(proc_root().join("uptime"))?;
+    let (uptime_seconds, idle_seconds) = parse_uptime(&uptime)?;
+    let idle_seconds = idle_seconds.ok_or(PsError::FailedToGetUptimeFromStat)?;
+    let duration = |seconds| {
+        Duration::try_from_secs_f64(seconds).map_err(|_| PsError::FailedToGetUptimeFromStat)
+    };
+    Ok((duration(uptime_seconds)?, duration(idle_seconds)?))
+}
+
+/// Returns the start time of a process from the system uptime and its `starttime` from `/proc/<pid>/stat`.
+///
+/// * `uptime_seconds` — First value of `/proc/uptime`  
+/// * `start_ticks` — [`ProcStat::starttime`], clock ticks after boot  
+/// * `system_clock_tick_rate` — Clock ticks per second from `sysconf(_SC_CLK_TCK)`
+fn get_start_time(
+    uptime_seconds: f64,
+    start_ticks: u64,
+    system_clock_tick_rate: f64,
+) -> Result<DateTime<Local>, PsError> {
+    // convert start_time to seconds since boot.
+    let start_time_in_seconds = start_ticks as f64 / system_clock_tick_rate;
+    let now = SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs_f64();
+    let boot_time = now - uptime_seconds;
+    let process_start_time = (boot_time + start_time_in_seconds) as u64;
+
+    let duration = Duration::from_secs(process_start_time);
+    let system_time = UNIX_EPOCH + duration;
+    let date_time: DateTime<Local> = system_time.into();
+
+    Ok(date_time)
+}
+
+/// Parses the contents of `/proc/meminfo` into a [`MemInfo`].
+///
+/// Lines look like `MemTotal:       16318480 kB`, unknown keys are ignored.
+fn parse_mem_info(meminfo: &str) -> Result<MemInfo, PsError> {
+    let mut fields: [Option<u64>; 7] = [None; 7];
+    const KEYS: [&str; 7] = [
+        "MemTotal",
+        "MemAvailable",
+        "MemFree",
+        "Buffers",
+        "Cached",
+        "SwapTotal",
+        "SwapFree",
+    ];
+
+    for line in meminfo.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        if let Some(index) = KEYS.iter().position(|k| *k == key) {
+            // Drop the trailing " kB" unit.
+            let value = value.split_whitespace().next().unwrap_or_default();
+            fields[index] = Some(value.parse()?);
+        }
+    }
+
+    let field = |index: usize| fields[index].ok_or(PsError::MissingMemInfoField(KEYS[index]));
+    Ok(MemInfo {
+        total_kb: field(0)?,
+        available_kb: field(1)?,
+        free_kb: field(2)?,
+        buffers_kb: field(3)?,
+        cached_kb: field(4)?,
+        swap_total_kb: field(5)?,
+        swap_free_kb: field(6)?,
+    })
+}
+
+/// Returns `MemTotal` from the contents of `/proc/meminfo` in bytes.
+///
+/// Unlike [`parse_mem_info`] this only needs the one line, so it still works
+/// on kernels missing some of the other keys.
+fn parse_mem_total(meminfo: &str) -> Option<u64> {
+    let mut value = find_status_field(meminfo, "MemTotal")?.split_whitespace();
+    let kb: u64 = value.next()?.parse().ok()?;
+    match value.next() {
+        Some("kB") | None => kb.checked_mul(1024),
+        Some(_) => None,
+    }
+}
+
+/// Reads system-wide memory statistics from `/proc/meminfo`.
+pub fn get_mem_info() -> Result<MemInfo, PsError> {
+    let meminfo = std::fs::read_to_string(proc_root().join("meminfo"))?;
+    parse_mem_info(&meminfo)
+}
+
+/// Returns the individual `argv` elements of a process.
+///
+/// `/proc/<pid>/cmdline` holds the arguments separated (and terminated) by NUL
+/// bytes, empty trailing elements are dropped.
+pub fn get_cmdline_argv(pid: Pid) -> Result<Vec<String>, PsError> {
+    let cmdline = std::fs::read(proc_root().join(pid.to_string()).join("cmdline"))?;
+    let mut argv: Vec<String> = cmdline
+        .split(|byte| *byte == b'\0')
+        // Note: from_utf8_lossy replaces invalid bytes rather than failing.
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect();
+
+    while argv.last().is_some_and(|arg| arg.is_empty()) {
+        argv.pop();
+    }
+    Ok(argv)
+}
+
+impl PsError {
+    /// Wraps an I/O error from reading `/proc/<pid>/<file>`, keeping
+    /// permission errors distinguishable from missing data.
+    fn from_proc_read(e: std::io::Error, pid: Pid, file: &'static str) -> PsError {
+        match e.kind() {
+            std::io::ErrorKind::PermissionDenied => PsError::PermissionDenied {
+                pid,
+                file: Some(file),
+            },
+            _ => PsError::FailedToReadFile(e),
+        }
+    }
+}
+
+/// Returns the kernel stack trace of a process from `/proc/<pid>/stack`.
+///
+/// The file only exists on kernels built with `CONFIG_STACKTRACE` and the
+/// format varies, so it's returned as-is. Reading it needs `CAP_SYS_PTRACE`
+/// on recent kernels, otherwise [`PsError::PermissionDenied`] is returned.
+pub fn get_process_stack(pid: Pid) -> Result<String, PsError> {
+    std::fs::read_to_string(proc_root().join(pid.to_string()).join("stack"))
+        .map_err(|e| PsError::from_proc_read(e, pid, "stack"))
+}
+
+/// Looks up the username of `uid` in the passwd database, `None` if it has
+/// no entry or the lookup fails.
+///
+/// Uses `getpwuid_r`, so it's safe to call from several threads, e.g. with
+/// the `parallel` feature.
+pub fn resolve_uid(uid: u32) -> Option<String> {
+    read_passwd(
+        |passwd, buf, buf_len, res| unsafe { libc::getpwuid_r(uid, passwd, buf, buf_len, res) },
+        |passwd| {
+            // Note: to_string_lossy converts the bytes it can to string or gives up
+            let name = unsafe { CStr::from_ptr(passwd.pw_name) };
+            name.to_string_lossy().into_owned()
+        },
+    )
+}
+
+/// Looks up the UID of the user `name`, the reverse of [`resolve_uid`].
+///
+/// Uses `getpwnam_r`, so like [`resolve_uid`] it's thread safe.
+pub fn resolve_username(name: &str) -> Option<u32> {
+    let name = CString::new(name).ok()?;
+    read_passwd(
+        |passwd, buf, buf_len, res| unsafe {
+            libc::getpwnam_r(name.as_ptr(), passwd, buf, buf_len, res)
+        },
+        |passwd| passwd.pw_uid,
+    )
+}
+
+/// Runs a `getpw*_r` `lookup` with a buffer that's grown until the entry
+/// fits, then passes the entry to `read`. `None` if there's no entry or the
+/// lookup fails.
+fn read_passwd<T>(
+    lookup: impl Fn(*mut libc::passwd, *mut libc::c_char, usize, *mut *mut libc::passwd) -> libc::c_int,
+    read: impl FnOnce(&libc::passwd) -> T,
+) -> Option<T> {
+    // Only a hint, and -1 if there's no limit.
+    let mut buf_len = match unsafe { libc::sysconf(libc::_SC_GETPW_R_SIZE_MAX) } {
+        len if len > 0 => len as usize,
+        _ => 1024,
+    };
+    loop {
+        let mut buf: Vec<libc::c_char> = vec![0; buf_len];
+        let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+        let mut res: *mut libc::passwd = std::ptr::null_mut();
+        // The *_r variants are thread safe because we provide our own buffer,
+        // the strings in `passwd` point into it.
+        let err = lookup(&mut passwd, buf.as_mut_ptr(), buf.len(), &mut res);
+        match err {
+            // The entry didn't fit, e.g. a long list of group members.
+            libc::ERANGE if buf_len < 1 << 20 => buf_len *= 2,
+            // Null without an error means there's no matching entry.
+            0 if !res.is_null() => return Some(read(&passwd)),
+            _ => return None,
+        }
+    }
+}
+
+/// Returns the current working directory of a process by resolving the
+/// `/proc/<pid>/cwd` link.
+///
+/// Resolving it needs ptrace access, so for other users' processes this
+/// returns [`PsError::PermissionDenied`] unless running as root.
+pub fn get_process_cwd(pid: Pid) -> Result<PathBuf, PsError> {
+    std::fs::read_link(proc_root().join(pid.to_string()).join("cwd"))
+        .map_err(|e| PsError::from_proc_read(e, pid, "cwd"))
+}
+
+/// Parses the contents of `/proc/<pid>/limits`.
+///
+/// Both the limit names and the values are padded with spaces, so the column
+/// boundaries are taken from the header line rather than splitting on
+/// whitespace.
+fn parse_limits(limits: &str) -> Result<Vec<ProcessLimit>, PsError> {
+    let mut lines = limits.lines();
+    let header = lines.next().ok_or(PsError::MalformedLimits)?;
+    let column = |name| header.find(name).ok_or(PsError::MalformedLimits);
+    let (soft_at, hard_at, units_at) = (
+        column("Soft Limit")?,
+        column("Hard Limit")?,
+        column("Units")?,
+    );
+
+    let value = |value: &str| match value {
+        "unlimited" => Ok(None),
+        value => value.parse().map(Some),
+    };
+
+    let mut process_limits = vec![];
+    for line in lines.filter(|line| !line.trim().is_empty()) {
+        let cell = |start: usize, end: usize| {
+            line.get(start..end.min(line.len()))
+                .unwrap_or_default()
+                .trim()
+        };
+        process_limits.push(ProcessLimit {
+            name: cell(0, soft_at).to_owned(),
+            soft: value(cell(soft_at, hard_at))?,
+            hard: value(cell(hard_at, units_at))?,
+            units: cell(units_at, line.len()).to_owned(),
+        });
+    }
+    Ok(process_limits)
+}
+
+/// Returns the soft `Max open files` limit from `/proc/<pid>/limits`, `None`
+/// if it's unlimited or missing.
+///
+/// Unlike [`parse_limits`] this doesn't depend on the header's column
+/// positions, the soft limit is just the first value after the name.
+fn find_fd_limit(limits: &str) -> Option<u64> {
+    limits
+        .lines()
+        .find_map(|line| line.strip_prefix("Max open files"))?
+        .split_whitespace()
+        .next()?
+        .parse()
+        .ok()
+}
+
+/// Returns the soft and hard resource limits of a process, like `ulimit -a`.
+pub fn get_process_limits(pid: Pid) -> Result<Vec<ProcessLimit>, PsError> {
+    let limits = std::fs::read_to_string(proc_root().join(pid.to_string()).join("limits"))?;
+    parse_limits(&limits)
+}
+
+impl Proto {
+    /// Name of the table under `/proc/<pid>/net/`.
+    fn table(self) -> &'static str {
+        match self {
+            Proto::Tcp => "tcp",
+            Proto::Tcp6 => "tcp6",
+            Proto::Udp => "udp",
+            Proto::Udp6 => "udp6",
+        }
+    }
+}
+
+impl TcpState {
+    /// Maps the hex code from the `st` column (`include/net/tcp_states.h`).
+    fn from_code(code: u8) -> Self {
+        match code {
+            0x01 => Self::Established,
+            0x02 => Self::SynSent,
+            0x03 => Self::SynRecv,
+            0x04 => Self::FinWait1,
+            0x05 => Self::FinWait2,
+            0x06 => Self::TimeWait,
+            0x07 => Self::Close,
+            0x08 => Self::CloseWait,
+            0x09 => Self::LastAck,
+            0x0A => Self::Listen,
+            0x0B => Self::Closing,
+            0x0C => Self::NewSynRecv,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+/// Parses an address like `0100007F:0035` from `/proc/<pid>/net/*`.
+///
+/// The IP is printed as 32-bit words in host byte order, while the port is
+/// already converted to a plain number.
+fn parse_net_addr(addr: &str) -> Result<SocketAddr, PsError> {
+    let (ip, port) = addr.split_once(':').ok_or(PsError::MalformedNetEntry)?;
+    let port = u16::from_str_radix(port, 16)?;
+    // One word for IPv4, four for IPv6.
+    if ip.len() != 8 && ip.len() != 32 {
+        return Err(PsError::MalformedNetEntry);
+    }
+
+    let mut bytes = vec![];
+    for word in 0..ip.len() / 8 {
+        let word = ip
+            .get(word * 8..word * 8 + 8)
+            .ok_or(PsError::MalformedNetEntry)?;
+        bytes.extend(u32::from_str_radix(word, 16)?.to_ne_bytes());
+    }
+
+    let ip = if let Ok(v4) = <[u8; 4]>::try_from(bytes.as_slice()) {
+        IpAddr::from(v4)
+    } else if let Ok(v6) = <[u8; 16]>::try_from(bytes.as_slice()) {
+        IpAddr::from(v6)
+    } else {
+        return Err(PsError::MalformedNetEntry);
+    };
+    Ok(SocketAddr::new(ip, port))
+}
+
+/// Parses one `/proc/<pid>/net/{tcp,tcp6,udp,udp6}` table, skipping the header.
+fn parse_net_table(proto: Proto, table: &str) -> Result<Vec<NetConnection>, PsError> {
+    let mut connections = vec![];
+    for line in table.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        // sl, local, remote, st, tx:rx, tr:when, retrnsmt, uid, timeout, inode
+        if fields.len() < 10 {
+            return Err(PsError::MalformedNetEntry);
+        }
+        connections.push(NetConnection {
+            proto,
+            local_addr: parse_net_addr(fields[1])?,
+            remote_addr: parse_net_addr(fields[2])?,
+            state: TcpState::from_code(u8::from_str_radix(fields[3], 16)?),
+            inode: fields[9].parse()?,
+        });
+    }
+    Ok(connections)
+}
+
+/// Returns the network connections owned by a process.
+///
+/// The `/proc/<pid>/net/` tables list every socket in the process's network
+/// namespace, so they are cross-referenced with the `socket:[<inode>]` links
+/// in `/proc/<pid>/fd/` to keep only the process's own sockets. Reading
+/// another user's `fd/` usually requires root.
+pub fn get_net_connections(pid: Pid) -> Result<Vec<NetConnection>, PsError> {
+    let proc_dir = proc_root().join(pid.to_string());
+
+    let mut inodes = HashSet::new();
+    for fd in std::fs::read_dir(proc_dir.join("fd"))? {
+        // Note: an fd closed while we iterate is simply skipped.
+        let Ok(target) = std::fs::read_link(fd?.path()) else {
+            continue;
+        };
+        let target = target.to_string_lossy();
+        if let Some(inode) = target
+            .strip_prefix("socket:[")
+            .and_then(|inode| inode.strip_suffix(']'))
+            .and_then(|inode| inode.parse::<u64>().ok())
+        {
+            inodes.insert(inode);
+        }
+    }
+
+    let mut connections = vec![];
+    for proto in [Proto::Tcp, Proto::Tcp6, Proto::Udp, Proto::Udp6] {
+        // IPv6 tables are missing when the kernel has IPv6 disabled.
+        let Ok(table) = std::fs::read_to_string(proc_dir.join("net").join(proto.table())) else {
+            continue;
+        };
+        connections.extend(
+            parse_net_table(proto, &table)?
+                .into_iter()
+                .filter(|connection| inodes.contains(&connection.inode)),
+        );
+    }
+    Ok(connections)
+}
+
+/// System-wide values read once per scan and shared by every process.
+struct ScanContext {
+    /// The proc mount, part of the [`START_TIMES`] key.
+    root: PathBuf,
+    /// `None` if `/proc/uptime` is unreadable, which only costs start times.
+    uptime_seconds: Option<f64>,
+    system_clock_tick_rate: f64,
+    /// `None` if the page size is unknown, which only costs the RSS column.
+    page_size: Option<u64>,
+    /// Physical memory in bytes, `None` if `/proc/meminfo` is unreadable,
+    /// which only costs `%MEM`.
+    mem_total: Option<u64>,
+}
+
+/// Returns the clock ticks per second from `sysconf(_SC_CLK_TCK)`.
+///
+/// The rate is fixed while the system is up, so a successful result is
+/// cached and later calls skip the syscall.
+fn clock_tick_rate() -> Result<f64, PsError> {
+    static TICK_RATE: OnceLock<f64> = OnceLock::new();
+    if let Some(&tick_rate) = TICK_RATE.get() {
+        return Ok(tick_rate);
+    }
+
+    // This is just for linux, windows might have an easier way of accessing this information.
+    let tick_rate = unsafe { libc::sysconf(libc::_SC_CLK_TCK) } as f64;
+    // If libc returns -1 that option does exist
+    if tick_rate == -1.0 {
+        // This is not thread safe
+        let err_num = unsafe { *libc::__errno_location() };
+        return Err(PsError::FailedToGetSysClockTickRate(err_num));
+    }
+    Ok(*TICK_RATE.get_or_init(|| tick_rate))
+}
+
+impl ScanContext {
+    /// Reads the shared values for a scan of the proc mount at `root`.
+    ///
+    /// Only a missing clock tick rate is fatal, an unreadable uptime is
+    /// pushed to `warnings` and leaves start times unknown.
+    fn new(root: &Path, warnings: &mut Vec<PsError>) -> Result<ScanContext, PsError> {
+        let system_clock_tick_rate = clock_tick_rate()?;
+
+        // Uptime is the same for every process, so read it once.
+        let uptime_seconds = match get_uptime(&root.join("uptime")) {
+            Ok(uptime_seconds) => Some(uptime_seconds),
+            Err(e) => {
+                warnings.push(e);
+                None
+            }
+        };
+
+        let page_size = match unsafe { libc::sysconf(libc::_SC_PAGESIZE) } {
+            -1 => None,
+            page_size => Some(page_size as u64),
+        };
+
+        let mem_total = std::fs::read_to_string(root.join("meminfo"))
+            .ok()
+            .and_then(|meminfo| parse_mem_total(&meminfo))
+            .filter(|&mem_total| mem_total > 0);
+
+        Ok(ScanContext {
+            root: root.to_path_buf(),
+            uptime_seconds,
+            system_clock_tick_rate,
+            page_size,
+            mem_total,
+        })
+    }
+}
+
+/// Start times from earlier scans, keyed on the proc mount, PID and
+/// [`ProcStat::starttime`].
+///
+/// The start time is derived from the current time and uptime, which are
+/// read at slightly different moments, so recomputing it can be off by a
+/// second from one scan to the next. `starttime` is fixed for the lifetime
+/// of a process, so the first result is kept and repeated scans, e.g. with
+/// `--watch`, show a stable value.
+static START_TIMES: OnceLock<Mutex<HashMap<StartTimeKey, DateTime<Local>>>> = OnceLock::new();
+
+/// Proc mount, PID and `starttime` ticks of a [`START_TIMES`] entry.
+type StartTimeKey = (PathBuf, Pid, u64);
+
+/// [`START_TIMES`] is cleared when it reaches this many entries, so exited
+/// processes don't pile up in a long-running monitor.
+const START_TIME_CACHE_LIMIT: usize = 1 << 16;
+
+impl ScanContext {
+    /// Returns the start time of `pid` from its `starttime` in clock ticks,
+    /// cached across scans. `None` if it isn't cached and the uptime is
+    /// unknown.
+    fn start_time(&self, pid: Pid, start_ticks: u64) -> Option<Result<DateTime<Local>, PsError>> {
+        let cache = START_TIMES.get_or_init(Mutex::default);
+        let key = (self.root.clone(), pid, start_ticks);
+        // A panic elsewhere can't leave the map inconsistent, keep using it.
+        let lock = || cache.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(&date_time) = lock().get(&key) {
+            return Some(Ok(date_time));
+        }
+
+        let res = get_start_time(
+            self.uptime_seconds?,
+            start_ticks,
+            self.system_clock_tick_rate,
+        );
+        if let Ok(date_time) = res {
+            let mut cache = lock();
+            if cache.len() >= START_TIME_CACHE_LIMIT {
+                cache.clear();
+            }
+            cache.insert(key, date_time);
+        }
+        Some(res)
+    }
+}
+
+/// Attempts to parse a single `/proc` entry into a [`Process`] struct, see
+/// [`read_process`].
+///
+/// Returns [`PsError::NotAProcess`] if the directory name is not a PID.
+fn get_process(
+    dir_ent: DirEntry,
+    context: &ScanContext,
+    scanner: &ProcScanner,
+    warnings: &mut Vec<PsError>,
+) -> Result<Process, PsError> {
+    // Only parse filenames if they are numbers (process').
+    let file_name = dir_ent.file_name();
+    let Ok(pid) = file_name.to_string_lossy().parse::<Pid>() else {
+        return Err(PsError::NotAProcess(
+            file_name.to_string_lossy().into_owned(),
+        ));
+    };
+
+    Ok(read_process(
+        pid,
+        &dir_ent.path(),
+        context,
+        scanner,
+        warnings,
+    ))
+}
+
+/// Reads the process directory `path` of `pid` into a [`Process`] struct.
+///
+/// This reads values from several `/proc/<pid>/...` files:
+/// - `cmdline`  
+/// - `exe` (symlink)  
+/// - `stat` (start time, parent PID, memory)  
+/// - `status` (state, `NoNewPrivs`, `Seccomp` and capabilities)  
+/// - `schedstat` (scheduler statistics)  
+/// - `wchan` (wait channel)  
+/// - directory metadata (UID → username)
+///
+/// Optional files are only read when enabled on the `scanner`.
+///
+/// Files that disappear during parsing leave their fields as `None`, errors
+/// that say more than that are pushed to `warnings`. That includes
+/// [`PsError::PermissionDenied`] for an unreadable `stat` or `status`, so
+/// sparse output can be told apart from missing data.
+fn read_process(
+    pid: Pid,
+    path: &Path,
+    context: &ScanContext,
+    scanner: &ProcScanner,
+    warnings: &mut Vec<PsError>,
+) -> Process {
+    let cmdline = path.join("cmdline");
+    let binary_path = path.join("exe");
+    let stat_path = path.join("stat");
+    let state_path = path.join("status");
+    let cgroup_path = path.join("cgroup");
+    let wchan_path = path.join("wchan");
+    let schedstat_path = path.join("schedstat");
+
+    let mut process = Process {
+        read_at: Some(Instant::now()),
+        read_time: Some(Local::now()),
+        ..Process::empty(pid)
+    };
+
+    // Read command line.
+    if let Ok(cmd) = std::fs::read_to_string(cmdline) {
+        process.cmdline = Some(cmd);
+    }
+
+    // Read executable symlink.
+    if let Ok(binary_path) = std::fs::read_link(binary_path) {
+        let (binary_path, deleted) = strip_deleted_suffix(binary_path);
+        process.binary_path = Some(binary_path);
+        process.binary_deleted = deleted;
+    }
+
+    // Extract owner name from UID.
+    if let Ok(metadata) = std::fs::metadata(path) {
+        process.owner = Some(user_name(metadata.uid()));
+    }
+
+    // Start time, parent PID and memory usage.
+    match read_with_retries(&stat_path, scanner.retries)
+        .map_err(|e| PsError::from_proc_read(e, pid, "stat"))
+        .and_then(|stat| ProcStat::parse(&stat))
+    {
+        Ok(stat) => {
+            process.ppid = Pid::new(stat.ppid);
+            process.pgid = Pid::new(stat.pgrp);
+            process.sid = Pid::new(stat.session);
+            process.name = Some(stat.comm);
+            process.vsz = Some(stat.vsize);
+            process.policy = Some(stat.policy);
+            process.last_cpu = u32::try_from(stat.processor).ok();
+            process.minor_faults = Some(stat.minflt);
+            process.major_faults = Some(stat.majflt);
+            // Kernel threads have no address space and report 0, processes
+            // we may not ptrace report 1 instead of the real addresses.
+            let address = |address: u64| (address > 1).then_some(address);
+            process.start_code = address(stat.startcode);
+            process.end_code = address(stat.endcode);
+            process.cpu_time = Some(Duration::from_secs_f64(
+                (stat.utime + stat.stime) as f64 / context.system_clock_tick_rate,
+            ));
+            // rss is reported in pages.
+            process.rss = context
+                .page_size
+                .map(|page_size| stat.rss.max(0) as u64 * page_size);
+            if let (Some(rss), Some(mem_total)) = (process.rss, context.mem_total) {
+                process.mem_percent = Some(rss as f64 / mem_total as f64 * 100.0);
+            }
+            match context.start_time(pid, stat.starttime) {
+                Some(Ok(date_time)) => process.start_time = Some(date_time),
+                Some(Err(e)) => warnings.push(e),
+                None => {}
+            }
+        }
+        Err(e) => warnings.push(e),
+    }
+
+    // Process state. Like stat, status is only unreadable on hardened
+    // kernels (`hidepid`), so report that instead of leaving the state blank.
+    match read_with_retries(&state_path, scanner.retries) {
+        Ok(state_res) => {
+            let status = ProcStatus::parse(&state_res);
+            process.state = status.state;
+            // Both are missing on kernels older than 4.10 and 3.8.
+            process.no_new_privs = status.no_new_privs;
+            process.seccomp_mode = status.seccomp;
+            process.cap_effective = status.cap_effective;
+            process.cap_permitted = status.cap_permitted;
+            process.uid = status.uid;
+            process.gid = status.gid;
+            process.voluntary_ctx_switches = status.voluntary_ctxt_switches;
+            process.nonvoluntary_ctx_switches = status.nonvoluntary_ctxt_switches;
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::PermissionDenied => {
+            warnings.push(PsError::from_proc_read(e, pid, "status"));
+        }
+        Err(_) => {}
+    }
+
+    // Scheduler statistics.
+    if let Some((cpu_time_ns, wait_time_ns, timeslices)) = std::fs::read_to_string(schedstat_path)
+        .ok()
+        .and_then(|schedstat| find_schedstat(&schedstat))
+    {
+        process.cpu_time_ns = Some(cpu_time_ns);
+        process.wait_time_ns = Some(wait_time_ns);
+        process.timeslices = Some(timeslices);
+    }
+
+    // Wait channel (opt-in), kernel threads are left out like in `ps`.
+    if scanner.wchan
+        && !process.is_kernel_thread()
+        && let Ok(wchan_res) = std::fs::read_to_string(wchan_path)
+    {
+        process.wchan = find_wchan(&wchan_res);
+    }
+
+    // Cgroup (opt-in), the file doesn't exist on older kernels.
+    if scanner.cgroup
+        && let Ok(cgroup_res) = std::fs::read_to_string(cgroup_path)
+    {
+        process.cgroup = find_cgroup(&cgroup_res);
+    }
+
+    // Storage I/O (opt-in), needs the same permissions as ptrace.
+    if scanner.io
+        && let Some((read_bytes, write_bytes)) = std::fs::read_to_string(path.join("io"))
+            .ok()
+            .and_then(|io| find_io_bytes(&io))
+    {
+        process.read_bytes = Some(read_bytes);
+        process.write_bytes = Some(write_bytes);
+    }
+
+    // Login UID (opt-in), `4294967295` (-1) if the process isn't part of a
+    // login session, which doesn't parse.
+    if scanner.loginuid
+        && let Some(loginuid) =
+            read_int::<u32>(&path.join("loginuid")).filter(|&uid| uid != u32::MAX)
+    {
+        process.loginuid = Some(loginuid);
+        process.login_user = Some(user_name(loginuid));
+    }
+
+    // OOM score, world-readable but missing on kernels without the OOM killer.
+    process.oom_score = read_int(&path.join("oom_score"));
+    process.oom_score_adj = read_int(&path.join("oom_score_adj"));
+
+    // Open file descriptors and their limit (opt-in).
+    if scanner.fds {
+        process.num_fd = std::fs::read_dir(path.join("fd"))
+            .ok()
+            .map(|fds| fds.count().try_into().unwrap_or(u32::MAX));
+        process.fd_limit = std::fs::read_to_string(path.join("limits"))
+            .ok()
+            .and_then(|limits| find_fd_limit(&limits));
+    }
+
+    // Working directory (opt-in). Kernel threads all have `/`, which says
+    // nothing, so leave them out.
+    if scanner.cwd && !process.is_kernel_thread() {
+        process.cwd = std::fs::read_link(path.join("cwd")).ok();
+    }
+
+    process
+}
+
+/// Pause before retrying a read in [`read_with_retries`].
+const RETRY_DELAY: Duration = Duration::from_millis(1);
+
+/// Reads `path` to a string, trying again up to `retries` times after a
+/// short pause if it's `NotFound`.
+fn read_with_retries(path: &Path, retries: u32) -> io::Result<String> {
+    let mut attempts_left = retries;
+    loop {
+        match std::fs::read_to_string(path) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound && attempts_left > 0 => {
+                attempts_left -= 1;
+                std::thread::sleep(RETRY_DELAY);
+            }
+            res => return res,
+        }
+    }
+}
+
+/// Returns the username of `uid`, or the UID as a string if it has no
+/// passwd entry.
+fn user_name(uid: u32) -> String {
+    resolve_uid(uid).unwrap_or_else(|| uid.to_string())
+}
+
+/// Reads a row per thread from `/proc/<pid>/task/`, sorted by thread ID.
+///
+/// Returns `None` if `task/` can't be read, e.g. because the process exited.
+fn read_threads(
+    pid: Pid,
+    path: &Path,
+    context: &ScanContext,
+    scanner: &ProcScanner,
+    warnings: &mut Vec<PsError>,
+) -> Option<Vec<Process>> {
+    let mut threads: Vec<Process> = std::fs::read_dir(path.join("task"))
+        .ok()?
+        .filter_map(|entry| {
+            let entry = entry.ok()?;
+            let tid = entry.file_name().to_string_lossy().parse::<Pid>().ok()?;
+            let mut thread = read_process(pid, &entry.path(), context, scanner, warnings);
+            thread.tid = Some(tid);
+            Some(thread)
+        })
+        .collect();
+    threads.sort_by_key(|thread| thread.tid);
+    Some(threads)
+}
+
+impl Default for ProcScanner {
+    fn default() -> Self {
+        ProcScanner {
+            root: None,
+            filter: None,
+            wchan: false,
+            cgroup: false,
+            threads: false,
+            io: false,
+            loginuid: false,
+            cwd: false,
+            fds: false,
+            retries: 1,
+        }
+    }
+}
+
+impl ProcScanner {
+    /// Creates a scanner with every optional field disabled.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a scanner reading from `root` instead of `/proc`.
+    ///
+    /// Shorthand for `ProcScanner::new().root(root)`.
+    pub fn with_root(root: impl Into<PathBuf>) -> Self {
+        Self::new().root(root)
+    }
+
+    /// Scan an alternate proc mount, such as a captured snapshot.
+    ///
+    /// Takes precedence over the `PROC_ROOT` environment variable.
+    pub fn root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.root = Some(root.into());
+        self
+    }
+
+    /// Only return processes matching `filter`. PID criteria are checked
+    /// before anything is read for a process.
+    pub fn filter(mut self, filter: Filter) -> Self {
+        self.filter = Some(filter);
+        self
+    }
+
+    /// Read the kernel function each process is blocked in from
+    /// `/proc/<pid>/wchan`.
+    pub fn wchan(mut self, enabled: bool) -> Self {
+        self.wchan = enabled;
+        self
+    }
+
+    /// Read the cgroup v2 path from `/proc/<pid>/cgroup`, useful for telling
+    /// which container a process belongs to.
+    pub fn cgroup(mut self, enabled: bool) -> Self {
+        self.cgroup = enabled;
+        self
+    }
+
+    /// Read the storage I/O counters from `/proc/<pid>/io`.
+    ///
+    /// Only root can read them for other users' processes, those are left
+    /// as `None`.
+    pub fn io(mut self, enabled: bool) -> Self {
+        self.io = enabled;
+        self
+    }
+
+    /// Read the audit login UID from `/proc/<pid>/loginuid`, to attribute
+    /// privileged processes to the user who logged in.
+    pub fn loginuid(mut self, enabled: bool) -> Self {
+        self.loginuid = enabled;
+        self
+    }
+
+    /// Resolve the working directory link `/proc/<pid>/cwd`, see
+    /// [`get_process_cwd`] for a single process.
+    pub fn cwd(mut self, enabled: bool) -> Self {
+        self.cwd = enabled;
+        self
+    }
+
+    /// Count the open file descriptors in `/proc/<pid>/fd/` and read their
+    /// limit from `/proc/<pid>/limits`.
+    ///
+    /// Listing `fd/` needs the same permissions as ptrace, so other users'
+    /// processes get no count unless running as root.
+    pub fn fds(mut self, enabled: bool) -> Self {
+        self.fds = enabled;
+        self
+    }
+
+    /// Retry reading `stat` and `status` this many times (1 by default) when
+    /// they are reported missing, which can be a race with the process
+    /// being set up or torn down. 0 disables retries.
+    pub fn retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Return one row per thread instead of per process, like `ps -T`.
+    ///
+    /// Each thread is read from `/proc/<pid>/task/<tid>/`, so this multiplies
+    /// the number of reads. Rows keep the process PID and have the thread ID
+    /// in [`Process::tid`]. PID criteria of the filter still apply to the
+    /// process, the others to each thread.
+    pub fn threads(mut self, enabled: bool) -> Self {
+        self.threads = enabled;
+        self
+    }
+
+    /// Reads and returns all processes from `/proc`, similar to `ps aux`.
+    ///
+    /// The proc mount is taken from, in order of precedence, [`ProcScanner::root`],
+    /// the `PROC_ROOT` environment variable, and finally `/proc`.
+    ///
+    /// This function:
+    /// 1. Reads `/proc`  
+    /// 2. Determines the system clock tick rate (`sysconf(_SC_CLK_TCK)`)  
+    /// 3. Collects all numeric directories and sorts them by PID  
+    /// 4. Attempts to parse them into [`Process`] structs, in that order  
+    ///
+    /// The scan is best effort: only failing to open the proc mount or to get
+    /// the clock tick rate returns an `Err`. Anything that can't be read for a
+    /// single process, or system-wide values such as the uptime and page size
+    /// that only feed per-process fields, leaves the affected fields as `None`.
+    /// Non-fatal errors are dropped, use
+    /// [`scan_with_errors`](ProcScanner::scan_with_errors) to handle them.
+    ///
+    /// Returns a vector of all successfully parsed processes in ascending PID
+    /// order, whatever order the filesystem lists them in. With the `parallel`
+    /// feature the directories are read on a rayon thread pool, which keeps
+    /// the same order.
+    pub fn scan(&self) -> Result<Vec<Process>, PsError> {
+        self.scan_with_errors().map(|(processes, _)| processes)
+    }
+
+    /// Reads the single process `pid` with this scanner's root and
+    /// optional fields, ignoring the filter.
+    ///
+    /// Returns [`PsError::ProcessGone`] if there's no such process. Non-fatal
+    /// errors are dropped, use
+    /// [`process_with_errors`](ProcScanner::process_with_errors) to get them.
+    pub fn process(&self, pid: Pid) -> Result<Process, PsError> {
+        self.process_with_errors(pid).map(|(process, _)| process)
+    }
+
+    /// Like [`process`](ProcScanner::process), but also returns the non-fatal
+    /// errors hit while reading it.
+    pub fn process_with_errors(&self, pid: Pid) -> Result<(Process, Vec<PsError>), PsError> {
+        let root = self.root.clone().unwrap_or_else(proc_root);
+        let path = root.join(pid.to_string());
+        match std::fs::metadata(&path) {
+            Ok(metadata) if metadata.is_dir() => {}
+            Ok(_) => return Err(PsError::ProcessGone(pid)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(PsError::ProcessGone(pid));
+            }
+            Err(e) => return Err(e.into()),
+        }
+
+        let mut warnings = vec![];
+        let context = ScanContext::new(&root, &mut warnings)?;
+        let process = read_process(pid, &path, &context, self, &mut warnings);
+        Ok((process, warnings))
+    }
+
+    /// Like [`scan`](ProcScanner::scan), but also returns the non-fatal errors
+    /// hit along the way, e.g. a `/proc` entry that
+    /// couldn't be read, a malformed `stat` file, or
+    /// [`PsError::PermissionDenied`] for a process hidden by `hidepid`.
+    pub fn scan_with_errors(&self) -> Result<(Vec<Process>, Vec<PsError>), PsError> {
+        let root = self.root.clone().unwrap_or_else(proc_root);
+        let res = std::fs::read_dir(&root)?;
+
+        let mut errors = vec![];
+        let context = ScanContext::new(&root, &mut errors)?;
+        let mut entries = vec![];
+        for content in res {
+            let content = match content {
+                Ok(content) => content,
+                Err(e) => {
+                    errors.push(e.into());
+                    continue;
+                }
+            };
+            // Only want directories. `self` and `thread-self` are symlinks to
+            // PID directories, following them would list those PIDs twice.
+            if !content
+                .file_type()
+                .is_ok_and(|file_type| file_type.is_dir())
+            {
+                continue;
+            }
+            // Directories that aren't PIDs, e.g. `sys`, aren't processes.
+            let Ok(pid) = content.file_name().to_string_lossy().parse::<Pid>() else {
+                continue;
+            };
+            entries.push((pid, content.path()));
+        }
+        // read_dir order depends on the filesystem, sort so output is stable.
+        entries.sort_unstable_by_key(|&(pid, _)| pid);
+
+        let read = |(pid, path): (Pid, PathBuf)| {
+            let mut warnings = vec![];
+            // Skip excluded PIDs before reading anything.
+            if let Some(filter) = &self.filter
+                && !filter.matches_pid(pid)
+            {
+                return (vec![], warnings);
+            }
+
+            let process = read_process(pid, &path, &context, self, &mut warnings);
+            let rows = if self.threads {
+                read_threads(process.pid, &path, &context, self, &mut warnings)
+                    .unwrap_or_else(|| vec![process])
+            } else {
+                vec![process]
+            };
+
+            let rows = rows
+                .into_iter()
+                .filter(|process| {
+                    self.filter
+                        .as_ref()
+                        .is_none_or(|filter| filter.matches(process))
+                })
+                .collect::<Vec<_>>();
+            (rows, warnings)
+        };
+
+        #[cfg(not(feature = "parallel"))]
+        let results: Vec<_> = entries.into_iter().map(read).collect();
+
+        // Every /proc/<pid> read is independent, so they can be spread across threads.
+        #[cfg(feature = "parallel")]
+        let results: Vec<_> = {
+            use rayon::prelude::*;
+            entries.into_par_iter().map(read).collect()
+        };
+
+        let mut vec_of_processs = vec![];
+        for (rows, warnings) in results {
+            vec_of_processs.extend(rows);
+            errors.extend(warnings);
+        }
+
+        Ok((vec_of_processs, errors))
+    }
+}
+
+/// Returns all processes where one of the `argv` elements is exactly `arg`.
+///
+/// Unlike a substring search over the whole command line, `--port` does not
+/// match a process started with `--port-range`.
+pub fn find_processes_by_cmdline_arg(arg: &str) -> Result<Vec<Process>, PsError> {
+    let processes = get_processes()?
+        .into_iter()
+        .filter(|process| process.argv().is_some_and(|argv| argv.contains(&arg)))
+        .collect();
+    Ok(processes)
+}
+
+/// Groups processes by owner with per-owner totals, sorted by username.
+///
+/// Owners that couldn't be resolved to a name are already stored as their
+/// numeric UID, processes without any owner information are grouped under `-`.
+pub fn group_by_owner(procs: Vec<Process>) -> Vec<ProcessGroup> {
+    let mut groups: BTreeMap<String, Vec<Process>> = BTreeMap::new();
+    for process in procs {
+        let owner = process.owner.clone().unwrap_or_else(|| "-".to_owned());
+        groups.entry(owner).or_default().push(process);
+    }
+
+    groups
+        .into_iter()
+        .map(|(owner, processes)| ProcessGroup {
+            summary: GroupSummary::of(&processes),
+            owner,
+            processes,
+        })
+        .collect()
+}
+
+/// Groups processes by executable, e.g. all nginx workers. Combine with
+/// [`GroupSummary::of`] for the resource use of each program.
+///
+/// Processes whose binary path is unknown, such as kernel threads, are
+/// grouped under `[unknown]`.
+pub fn group_by_binary(procs: Vec<Process>) -> HashMap<PathBuf, Vec<Process>> {
+    let mut groups: HashMap<PathBuf, Vec<Process>> = HashMap::new();
+    for process in procs {
+        let binary_path = process
+            .binary_path
+            .clone()
+            .unwrap_or_else(|| PathBuf::from("[unknown]"));
+        groups.entry(binary_path).or_default().push(process);
+    }
+    groups
+}
+
+impl GroupSummary {
+    /// Summarizes a group of processes, e.g. the values of
+    /// [`group_by_binary`].
+    pub fn of(procs: &[Process]) -> Self {
+        let now = Local::now();
+        Self {
+            count: procs.len(),
+            total_rss: procs.iter().filter_map(|process| process.rss).sum(),
+            total_cpu_time: procs.iter().filter_map(|process| process.cpu_time).sum(),
+            total_cpu_percent: procs
+                .iter()
+                .filter_map(|process| process.cpu_percent_at(now))
+                .sum(),
+        }
+    }
+}
+
+/// Returns all processes that have `path` open, like `lsof <path>`.
+///
+/// Processes that exit during the search are skipped.
+pub fn find_processes_with_file(path: &Path) -> Result<Vec<Process>, PsError> {
+    let mut processes = vec![];
+    for process in get_processes()? {
+        match process.has_open_file(path) {
+            Ok(true) => processes.push(process),
+            Ok(false) => {}
+            Err(PsError::FailedToReadFile(e)) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(processes)
+}
+
+/// Lists zombie processes paired with the parent that hasn't reaped them,
+/// as `(zombie pid, parent pid)`.
+///
+/// A parent showing up many times is the one to look at when zombies pile up.
+pub fn zombie_parents(procs: &[Process]) -> Vec<(Pid, Pid)> {
+    procs
+        .iter()
+        .filter(|process| process.state == Some(ProcessState::Zombie))
+        .filter_map(|process| Some((process.pid, process.ppid?)))
+        .collect()
+}
+
+impl<'a> ProcessTree<'a> {
+    pub fn new(processes: &'a [Process]) -> Self {
+        let pids: HashSet<Pid> = processes.iter().map(|process| process.pid).collect();
+        let mut children: HashMap<Pid, Vec<usize>> = HashMap::new();
+        let mut roots = vec![];
+        for (i, process) in processes.iter().enumerate() {
+            match process.ppid {
+                Some(ppid) if pids.contains(&ppid) && ppid != process.pid => {
+                    children.entry(ppid).or_default().push(i);
+                }
+                _ => roots.push(i),
+            }
+        }
+        ProcessTree {
+            processes,
+            children,
+            roots,
+        }
+    }
+
+    /// Processes without a parent in the set, in their original order.
+    pub fn roots(&self) -> impl Iterator<Item = &'a Process> + '_ {
+        self.roots.iter().map(|&i| &self.processes[i])
+    }
+
+    /// The direct children of `pid`, in their original order.
+    pub fn children(&self, pid: Pid) -> impl Iterator<Item = &'a Process> + '_ {
+        self.children
+            .get(&pid)
+            .into_iter()
+            .flatten()
+            .map(|&i| &self.processes[i])
+    }
+
+    /// Writes the process at `i` and everything below it. `prefix` holds
+    /// the connectors of the ancestors, `last` is `None` for roots.
+    fn write_subtree(
+        &self,
+        f: &mut fmt::Formatter,
+        i: usize,
+        prefix: &str,
+        last: Option<bool>,
+        visited: &mut HashSet<usize>,
+    ) -> fmt::Result {
+        // A PID reused while scanning can make a process its own ancestor.
+        if !visited.insert(i) {
+            return Ok(());
+        }
+
+        let process = &self.processes[i];
+        let (connector, child_prefix) = match last {
+            None => ("", String::new()),
+            Some(true) => ("└─ ", format!("{prefix}   ")),
+            Some(false) => ("├─ ", format!("{prefix}│  ")),
+        };
+        writeln!(
+            f,
+            "{prefix}{connector}{} {}",
+            process.pid,
+            escape_control(process.name.as_deref().unwrap_or("-"))
+        )?;
+
+        let children = self
+            .children
+            .get(&process.pid)
+            .map_or(&[][..], Vec::as_slice);
+        for (n, &child) in children.iter().enumerate() {
+            let last = n + 1 == children.len();
+            self.write_subtree(f, child, &child_prefix, Some(last), visited)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Display for ProcessTree<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut visited = HashSet::new();
+        for &root in &self.roots {
+            self.write_subtree(f, root, "", None, &mut visited)?;
+        }
+
+        // Processes in a parent cycle aren't reachable from any root, print
+        // each cycle starting from its first process.
+        for i in 0..self.processes.len() {
+            if !visited.contains(&i) {
+                self.write_subtree(f, i, "", None, &mut visited)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ProcessSet {
+    pub fn get(&self, pid: Pid) -> Option<&Process> {
+        self.0.get(&pid)
+    }
+
+    pub fn contains(&self, pid: Pid) -> bool {
+        self.0.contains_key(&pid)
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The processes in no particular order.
+    pub fn iter(&self) -> impl Iterator<Item = &Process> {
+        self.0.values()
+    }
+
+    /// Compares this snapshot with the newer `other`.
+    ///
+    /// Processes are matched with [`Process::is_same_process_as`], so a PID
+    /// that now belongs to another process counts as removed and added.
+    pub fn diff(&self, other: &ProcessSet) -> ProcessDiff {
+        let same = |process: &Process, set: &ProcessSet| {
+            set.get(process.pid)
+                .is_some_and(|other| other.is_same_process_as(process))
+        };
+        let cpu_percent = |process: &Process| {
+            process
+                .cpu_percent_at(process.read_time?)
+                .map(|percent| format!("{percent:.1}"))
+        };
+        let mut diff = ProcessDiff {
+            added: other
+                .iter()
+                .filter(|process| !same(process, self))
+                .cloned()
+                .collect(),
+            removed: self
+                .iter()
+                .filter(|process| !same(process, other))
+                .cloned()
+                .collect(),
+            changed_state: self
+                .iter()
+                .filter_map(|old| {
+                    let new = other.get(old.pid)?;
+                    let changed = old.state != new.state || cpu_percent(old) != cpu_percent(new);
+                    (old.is_same_process_as(new) && changed).then(|| (old.clone(), new.clone()))
+                })
+                .collect(),
+        };
+        diff.added.sort();
+        diff.removed.sort();
+        diff.changed_state.sort_by_key(|(old, _)| old.pid);
+        diff
+    }
+}
+
+/// `newer - older` is [`older.diff(&newer)`](ProcessSet::diff), what
+/// changed going from `older` to `newer`.
+impl std::ops::Sub for &ProcessSet {
+    type Output = ProcessDiff;
+
+    fn sub(self, older: &ProcessSet) -> ProcessDiff {
+        older.diff(self)
+    }
+}
+
+impl std::ops::Sub for ProcessSet {
+    type Output = ProcessDiff;
+
+    fn sub(self, older: ProcessSet) -> ProcessDiff {
+        &self - &older
+    }
+}
+
+impl From<Vec<Process>> for ProcessSet {
+    fn from(procs: Vec<Process>) -> Self {
+        ProcessSet(
+            procs
+                .into_iter()
+                .map(|process| (process.pid, process))
+                .collect(),
+        )
+    }
+}
+
+/// Keeps the first `n` processes, e.g. the top memory users after sorting
+/// by RSS. Unsorted it takes the first `n` in scan order.
+pub fn top_n(mut procs: Vec<Process>, n: usize) -> Vec<Process> {
+    procs.truncate(n);
+    procs
+}
+
+/// Keeps the processes started after `cutoff`, e.g. the ones started in the
+/// last five minutes. Processes with an unknown start time are dropped.
+pub fn filter_started_after(procs: Vec<Process>, cutoff: DateTime<Local>) -> Vec<Process> {
+    procs
+        .into_iter()
+        .filter(|process| {
+            process
+                .start_time
+                .is_some_and(|start_time| start_time > cutoff)
+        })
+        .collect()
+}
+
+/// Keeps the processes whose resident set size in bytes is at least `min`
+/// and at most `max`, e.g. the ones using more than 100 MiB. Processes with
+/// an unknown RSS are dropped unless both bounds are `None`.
+pub fn filter_by_rss_range(
+    procs: Vec<Process>,
+    min: Option<u64>,
+    max: Option<u64>,
+) -> Vec<Process> {
+    if min.is_none() && max.is_none() {
+        return procs;
+    }
+    procs
+        .into_iter()
+        .filter(|process| {
+            process.rss.is_some_and(|rss| {
+                min.is_none_or(|min| rss >= min) && max.is_none_or(|max| rss <= max)
+            })
+        })
+        .collect()
+}
+
+/// Reads a single process from `/proc/<pid>`.
+///
+/// Returns [`PsError::ProcessGone`] if there's no such process. Like a scan
+/// this is best effort, fields that can't be read are left as `None` and
+/// optional fields aren't read.
+pub fn get_process_by_pid(pid: Pid) -> Result<Process, PsError> {
+    ProcScanner::new().process(pid)
+}
+
+/// Reads the calling process, resolving the `/proc/self` link.
+///
+/// A process can always read its own proc files, so unlike for other
+/// processes the command line and binary path are always filled in.
+pub fn current_process() -> Result<Process, PsError> {
+    let pid = std::fs::read_link(proc_root().join("self"))?
+        .to_string_lossy()
+        .parse()?;
+    get_process_by_pid(pid)
+}
+
+/// Reads and returns all processes from `/proc`, similar to `ps aux`, in
+/// ascending PID order.
+///
+/// Optional fields are left as `None`, use [`ProcScanner`] to enable them.
+/// Like [`ProcScanner::scan`] this is best effort and never fails because of
+/// a single process.
+pub fn get_processes() -> Result<Vec<Process>, PsError> {
+    ProcScanner::new().scan()
+}
+
+/// Like [`get_processes`], but also returns the non-fatal errors.
+///
+/// Returns the processes that could be read along with every error hit. If
+/// the scan couldn't start at all, e.g. `/proc` isn't mounted, there are no
+/// processes and the fatal error is the only one.
+pub fn get_processes_with_errors() -> (Vec<Process>, Vec<PsError>) {
+    ProcScanner::new()
+        .scan_with_errors()
+        .unwrap_or_else(|e| (vec![], vec![e]))
+}
+
+/// Async version of [`get_processes`] for programs running on `tokio`.
+///
+/// The `/proc` reads are blocking, so they run on tokio's blocking thread
+/// pool instead of stalling the runtime thread.
+#[cfg(feature = "tokio")]
+pub async fn get_processes_async() -> Result<Vec<Process>, PsError> {
+    tokio::task::spawn_blocking(get_processes).await?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const STAT: &str = "42 (sleep) S 1 42 42 0 -1 4194304 81 0 0 0 0 0 0 0 20 0 1 0 113500 \
+        2703360 327 18446744073709551615 1 1 0 0 0 0 0 0 0 0 0 0 17 0 0 0 0 0 0 1 1 1 1 1 1 1 0";
+
+    /// Creates an empty proc mount for a test under the temp directory.
+    fn fixture_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("ps-{name}-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("uptime"), "1000.00 2000.00\n").unwrap();
+        root
+    }
+
+    #[test]
+    fn scan_skips_self_symlink() {
+        let root = fixture_root("self-symlink");
+        std::fs::create_dir(root.join("42")).unwrap();
+        std::fs::write(root.join("42/stat"), STAT).unwrap();
+        std::os::unix::fs::symlink("42", root.join("self")).unwrap();
+        std::os::unix::fs::symlink("42", root.join("thread-self")).unwrap();
+
+        let (processes, _) = ProcScanner::with_root(&root).scan_with_errors().unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let pids: Vec<u32> = processes.iter().map(|process| process.pid.get()).collect();
+        assert_eq!(pids, [42]);
+    }
+
+    #[test]
+    fn scan_without_uptime_leaves_start_time_unknown() {
+        let root = fixture_root("no-uptime");
+        std::fs::remove_file(root.join("uptime")).unwrap();
+        std::fs::create_dir(root.join("42")).unwrap();
+        std::fs::write(root.join("42/stat"), STAT).unwrap();
+
+        let (processes, errors) = ProcScanner::with_root(&root).scan_with_errors().unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(processes.len(), 1);
+        assert_eq!(processes[0].pid(), Pid(42));
+        assert_eq!(processes[0].start_time(), None);
+        assert!(errors.iter().any(
+            |e| matches!(e, PsError::FailedToReadFile(e) if e.kind() == io::ErrorKind::NotFound)
+        ));
+    }
+
+    #[test]
+    fn refresh_reads_from_the_scanner_root() {
+        let root = fixture_root("refresh");
+        std::fs::create_dir(root.join("42")).unwrap();
+        std::fs::write(root.join("42/stat"), STAT).unwrap();
+        let scanner = ProcScanner::with_root(&root);
+        let mut process = scanner.process(Pid(42)).unwrap();
+        assert_eq!(process.vsz_bytes(), Some(2_703_360));
+
+        std::fs::write(root.join("42/stat"), STAT.replacen("2703360", "4096", 1)).unwrap();
+        let (refreshed, _) = process.refresh_with_errors(&scanner).unwrap();
+        assert!(refreshed);
+        assert_eq!(process.vsz_bytes(), Some(4096));
+
+        std::fs::remove_dir_all(root.join("42")).unwrap();
+        let refreshed = process.refresh(&scanner).unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+        assert!(!refreshed);
+    }
+
+    #[test]
+    fn json_escapes_quotes_backslashes_and_control_characters() {
+        assert_eq!(json_string(r#"a "b" \c"#), r#""a \"b\" \\c""#);
+        assert_eq!(json_string("tab\tnl\ncr\r"), r#""tab\tnl\ncr\r""#);
+        assert_eq!(json_string("\x01\x1b[2J"), r#""\u0001\u001b[2J""#);
+
+        let process = Process {
+            name: Some("evil\"name".to_owned()),
+            ..ProcessBuilder::new(Pid(42))
+                .cmdline("sh\0-c\0echo \"hi\" \\\n\0")
+                .build()
+        };
+        let json = process.to_json();
+        assert!(json.contains(r#""name":"evil\"name""#), "{json}");
+        assert!(
+            json.contains(r#""argv":["sh","-c","echo \"hi\" \\\n"]"#),
+            "{json}"
+        );
+        assert!(!json.contains('\n'));
+    }
+
+    #[test]
+    fn csv_quotes_commas_and_quotes() {
+        let processes = [ProcessBuilder::new(Pid(42))
+            .owner("a,b")
+            .cmdline("sh\0-c\0echo \"x\"\ndone\0")
+            .build()];
+        let mut out = Vec::new();
+        ProcessTable::new(&processes)
+            .columns(&[Column::Pid, Column::Owner, Column::Cmdline])
+            .write_csv(&mut out)
+            .unwrap();
+        // Newlines are escaped like in the table before quoting, so each
+        // process stays on one line.
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "PID,Owner,Cmdline\n42,\"a,b\",\"sh -c echo \"\"x\"\"\\x0adone\"\n"
+        );
+        assert_eq!(csv_field("line\nbreak"), "\"line\nbreak\"");
+    }
+
+    #[test]
+    fn diff_lists_added_removed_and_changed_processes() {
+        let boot = Local::now() - chrono::TimeDelta::hours(1);
+        let process = |pid, state, cpu_secs| Process {
+            state: Some(state),
+            start_time: Some(boot),
+            cpu_time: Some(Duration::from_secs(cpu_secs)),
+            read_time: Some(boot + chrono::TimeDelta::seconds(100)),
+            ..Process::empty(Pid(pid))
+        };
+        let older = ProcessSet::from(vec![
+            process(1, ProcessState::Sleeping, 0),
+            process(2, ProcessState::Sleeping, 0),
+            process(3, ProcessState::Sleeping, 0),
+            process(4, ProcessState::Running, 10),
+            process(5, ProcessState::Sleeping, 0),
+        ]);
+        let newer = ProcessSet::from(vec![
+            process(1, ProcessState::Sleeping, 0),
+            process(3, ProcessState::Running, 0),
+            // Same snapshot time, so 20s of CPU time means more usage.
+            process(4, ProcessState::Running, 20),
+            // PID 5 reused by a process started later.
+            Process {
+                start_time: Some(boot + chrono::TimeDelta::seconds(50)),
+                ..process(5, ProcessState::Sleeping, 0)
+            },
+            process(6, ProcessState::Sleeping, 0),
+        ]);
+
+        let diff = &newer - &older;
+        let pids = |processes: &[Process]| -> Vec<u32> {
+            processes.iter().map(|process| process.pid.get()).collect()
+        };
+        assert_eq!(pids(&diff.added), [5, 6]);
+        assert_eq!(pids(&diff.removed), [2, 5]);
+        let changed: Vec<(u32, Option<ProcessState>)> = diff
+            .changed_state
+            .iter()
+            .map(|(old, new)| (old.pid.get(), new.state))
+            .collect();
+        assert_eq!(
+            changed,
+            [
+                (3, Some(ProcessState::Running)),
+                (4, Some(ProcessState::Running))
+            ]
+        );
+        assert_eq!(diff.removed[1].start_time, Some(boot));
+
+        let same = older.diff(&older);
+        assert!(same.added.is_empty() && same.removed.is_empty() && same.changed_state.is_empty());
+
+        // The other way around, what's added and removed swaps.
+        let reverse = &older - &newer;
+        assert_eq!(pids(&reverse.added), [2, 5]);
+        assert_eq!(pids(&reverse.removed), [5, 6]);
+    }
+
+    #[test]
+    fn parse_limits_reads_padded_columns() {
+        let limits = concat!(
+            "Limit                     Soft Limit           Hard Limit           Units     \n",
+            "Max cpu time              unlimited            unlimited            seconds   \n",
+            "Max stack size            8388608              unlimited            bytes     \n",
+            "Max processes             23961                23961                processes \n",
+            "Max open files            20000                20000                files     \n",
+            "Max nice priority         0                    0                    \n",
+        );
+        let limits = parse_limits(limits).unwrap();
+        assert_eq!(limits.len(), 5);
+        let limit = |name: &str, soft, hard, units: &str| ProcessLimit {
+            name: name.to_owned(),
+            soft,
+            hard,
+            units: units.to_owned(),
+        };
+        assert_eq!(limits[0], limit("Max cpu time", None, None, "seconds"));
+        assert_eq!(
+            limits[1],
+            limit("Max stack size", Some(8_388_608), None, "bytes")
+        );
+        assert_eq!(
+            limits[2],
+            limit("Max processes", Some(23961), Some(23961), "processes")
+        );
+        assert_eq!(
+            limits[3],
+            limit("Max open files", Some(20000), Some(20000), "files")
+        );
+        // Unitless limits have an empty units column.
+        assert_eq!(limits[4], limit("Max nice priority", Some(0), Some(0), ""));
+
+        assert!(matches!(
+            parse_limits("Limit Soft\n"),
+            Err(PsError::MalformedLimits)
+        ));
+    }
+
+    #[test]
+    fn parse_mem_info_reads_kb_values() {
+        let meminfo = "MemTotal:       16318480 kB\n\
+            MemFree:         1234567 kB\n\
+            MemAvailable:    8765432 kB\n\
+            Buffers:          123456 kB\n\
+            Cached:          4567890 kB\n\
+            SwapCached:            0 kB\n\
+            SwapTotal:       2097148 kB\n\
+            SwapFree:        2097000 kB\n\
+            HugePages_Total:       0\n";
+        assert_eq!(
+            parse_mem_info(meminfo).unwrap(),
+            MemInfo {
+                total_kb: 16_318_480,
+                available_kb: 8_765_432,
+                free_kb: 1_234_567,
+                buffers_kb: 123_456,
+                cached_kb: 4_567_890,
+                swap_total_kb: 2_097_148,
+                swap_free_kb: 2_097_000,
+            }
+        );
+        assert_eq!(parse_mem_total(meminfo), Some(16_318_480 * 1024));
+
+        // Older kernels have no MemAvailable.
+        let old = meminfo.replace("MemAvailable:", "Unknown:");
+        assert!(matches!(
+            parse_mem_info(&old),
+            Err(PsError::MissingMemInfoField("MemAvailable"))
+        ));
+        let malformed = meminfo.replace("1234567 kB", "many kB");
+        assert!(matches!(
+            parse_mem_info(&malformed),
+            Err(PsError::FailedToParseAsInt(_))
+        ));
+    }
+
+    #[test]
+    fn find_cgroup_reads_the_unified_hierarchy() {
+        let v2 = "0::/system.slice/docker-abc.scope\n";
+        assert_eq!(
+            find_cgroup(v2).as_deref(),
+            Some("/system.slice/docker-abc.scope")
+        );
+
+        let v1 = "12:pids:/user.slice/user-1000.slice\n\
+            11:memory:/user.slice\n\
+            1:name=systemd:/user.slice/user-1000.slice/session-2.scope\n";
+        assert_eq!(find_cgroup(v1), None);
+
+        // Hybrid setups list the v1 controllers and the unified line.
+        let hybrid = format!("{v1}0::/user.slice/user-1000.slice/session-2.scope\n");
+        assert_eq!(
+            find_cgroup(&hybrid).as_deref(),
+            Some("/user.slice/user-1000.slice/session-2.scope")
+        );
+    }
+
+    #[test]
+    fn format_bytes_rounds_like_ps() {
+        for (bytes, formatted) in [
+            (0, "0B"),
+            (1023, "1023B"),
+            (1024, "1.0K"),
+            (1075, "1.0K"),
+            (1076, "1.1K"),
+            // Just under 1023.95K still rounds down.
+            (1_048_524, "1023.9K"),
+            (1_048_525, "1.0M"),
+            (1 << 20, "1.0M"),
+            (10_433_331, "9.9M"),
+            (10_433_332, "10.0M"),
+            (1 << 30, "1.0G"),
+            (u64::MAX, "16.0E"),
+        ] {
+            assert_eq!(format_bytes(bytes), formatted, "{bytes}");
+        }
+    }
+
+    #[test]
+    fn format_cpu_time_rolls_over_to_days() {
+        for (seconds, formatted) in [
+            (0.0, "00:00:00"),
+            (59.9, "00:00:59"),
+            (3599.0, "00:59:59"),
+            (3600.0, "01:00:00"),
+            (86_399.0, "23:59:59"),
+            (86_400.0, "1-00:00:00"),
+            (12.0 * 86_400.0 + 3723.0, "12-01:02:03"),
+        ] {
+            assert_eq!(
+                format_cpu_time(Duration::from_secs_f64(seconds)),
+                formatted,
+                "{seconds}"
+            );
+        }
+    }
+
+    #[test]
+    fn csv_prints_memory_in_bytes() {
+        let processes = [Process {
+            rss: Some(10_433_332),
+            ..Process::empty(Pid(42))
+        }];
+        let table = ProcessTable::new(&processes).columns(&[Column::Pid, Column::Rss]);
+        let mut csv = Vec::new();
+        table.write_csv(&mut csv).unwrap();
+        assert_eq!(String::from_utf8(csv).unwrap(), "PID,RSS\n42,10433332\n");
+        let mut tsv = Vec::new();
+        table.write_delimited(&mut tsv, '\t').unwrap();
+        assert_eq!(String::from_utf8(tsv).unwrap(), "PID\tRSS\n42\t10433332\n");
+        assert!(table.to_string().contains("10.0M"));
+    }
+
+    #[test]
+    fn filter_needs_every_criterion_and_known_values() {
+        let bash = Process {
+            name: Some("bash".to_owned()),
+            owner: Some("alice".to_owned()),
+            state: Some(ProcessState::Sleeping),
+            rss: Some(4 << 20),
+            ..Process::empty(Pid(42))
+        };
+        let unknown = Process::empty(Pid(43));
+
+        assert!(Filter::default().matches(&bash));
+        assert!(unknown.matches(&Filter::default()));
+
+        let filter = Filter {
+            owner: Some("alice".to_owned()),
+            name_substring: Some("as".to_owned()),
+            state: Some(ProcessState::Sleeping),
+            min_rss: Some(1 << 20),
+            pid_in: Some(vec![Pid(42), Pid(43)]),
+        };
+        assert!(bash.matches(&filter));
+        // Fields that couldn't be read don't match a set criterion.
+        assert!(!unknown.matches(&filter));
+        for filter in [
+            Filter {
+                owner: Some("bob".to_owned()),
+                ..filter.clone()
+            },
+            Filter {
+                name_substring: Some("zsh".to_owned()),
+                ..filter.clone()
+            },
+            Filter {
+                state: Some(ProcessState::Running),
+                ..filter.clone()
+            },
+            Filter {
+                min_rss: Some(8 << 20),
+                ..filter.clone()
+            },
+            Filter {
+                pid_in: Some(vec![Pid(1)]),
+                ..filter.clone()
+            },
+        ] {
+            assert!(!bash.matches(&filter), "{filter:?}");
+        }
+        for filter in [
+            Filter {
+                owner: Some("alice".to_owned()),
+                ..Filter::default()
+            },
+            Filter {
+                min_rss: Some(0),
+                ..Filter::default()
+            },
+        ] {
+            assert!(!unknown.matches(&filter), "{filter:?}");
+        }
+    }
+
+    #[test]
+    fn group_by_owner_sums_each_owner() {
+        let start = Local::now() - chrono::TimeDelta::seconds(100);
+        let process = |pid, owner: Option<&str>, rss, cpu_secs| Process {
+            owner: owner.map(str::to_owned),
+            rss,
+            start_time: Some(start),
+            cpu_time: Some(Duration::from_secs(cpu_secs)),
+            ..Process::empty(Pid(pid))
+        };
+        let groups = group_by_owner(vec![
+            process(1, Some("root"), Some(1 << 20), 10),
+            process(2, Some("alice"), Some(2 << 20), 0),
+            process(3, Some("root"), None, 5),
+            process(4, None, Some(1024), 0),
+        ]);
+
+        let owners: Vec<&str> = groups.iter().map(|group| group.owner.as_str()).collect();
+        assert_eq!(owners, ["-", "alice", "root"]);
+        let root = &groups[2];
+        assert_eq!(root.processes.len(), 2);
+        assert_eq!(root.summary.count, 2);
+        assert_eq!(root.summary.total_rss, 1 << 20);
+        assert_eq!(root.summary.total_cpu_time, Duration::from_secs(15));
+        // 15s of CPU time over about 100s.
+        assert!((root.summary.total_cpu_percent - 15.0).abs() < 0.5);
+        assert_eq!(groups[0].summary.total_rss, 1024);
+    }
+
+    #[test]
+    fn zombie_parents_pairs_zombies_with_their_parent() {
+        let process = |pid, ppid: Option<u32>, state| Process {
+            ppid: ppid.map(Pid),
+            state: Some(state),
+            ..Process::empty(Pid(pid))
+        };
+        let processes = [
+            process(1, None, ProcessState::Sleeping),
+            process(10, Some(1), ProcessState::Zombie),
+            process(11, Some(1), ProcessState::Running),
+            process(12, Some(5), ProcessState::Zombie),
+            // A zombie whose parent is unknown can't be paired.
+            process(13, None, ProcessState::Zombie),
+        ];
+        assert_eq!(
+            zombie_parents(&processes),
+            [(Pid(10), Pid(1)), (Pid(12), Pid(5))]
+        );
+    }
+
+    #[test]
+    fn top_n_and_filters_keep_matching_processes() {
+        let now = Local::now();
+        let process = |pid, rss, age: Option<i64>| Process {
+            rss,
+            start_time: age.map(|age| now - chrono::TimeDelta::seconds(age)),
+            ..Process::empty(Pid(pid))
+        };
+        let processes = vec![
+            process(1, Some(100), Some(3600)),
+            process(2, Some(200), Some(60)),
+            process(3, None, Some(10)),
+            process(4, Some(300), None),
+        ];
+        let pids = |processes: Vec<Process>| -> Vec<u32> {
+            processes.iter().map(|process| process.pid.get()).collect()
+        };
+
+        assert_eq!(pids(top_n(processes.clone(), 2)), [1, 2]);
+        assert_eq!(pids(top_n(processes.clone(), 10)), [1, 2, 3, 4]);
+        assert!(top_n(processes.clone(), 0).is_empty());
+
+        let cutoff = now - chrono::TimeDelta::seconds(300);
+        assert_eq!(
+            pids(filter_started_after(processes.clone(), cutoff)),
+            [2, 3]
+        );
+
+        let in_range = |min, max| pids(filter_by_rss_range(processes.clone(), min, max));
+        // No bounds keeps the unknown RSS too.
+        assert_eq!(in_range(None, None), [1, 2, 3, 4]);
+        assert_eq!(in_range(Some(200), None), [2, 4]);
+        assert_eq!(in_range(None, Some(200)), [1, 2]);
+        assert_eq!(in_range(Some(150), Some(250)), [2]);
+        assert!(in_range(Some(250), Some(150)).is_empty());
+    }
+
+    #[test]
+    fn parse_uptime_only_needs_the_uptime() {
+        assert_eq!(
+            parse_uptime("48267.42 190000.10\n").unwrap(),
+            (48267.42, Some(190_000.10))
+        );
+        assert_eq!(parse_uptime("48267.42\n").unwrap(), (48267.42, None));
+        assert_eq!(parse_uptime("48267.42 idle\n").unwrap(), (48267.42, None));
+        assert!(parse_uptime("").is_err());
+        assert!(parse_uptime("soon 1.0").is_err());
+    }
+
+    #[test]
+    fn write_table_without_processes_writes_header() {
+        let mut out = Vec::new();
+        write_table(&[], &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert_eq!(out.lines().count(), 1);
+        assert!(out.starts_with("PID "));
+        assert!(out.trim_end().ends_with("Cmdline"));
+    }
+
+    #[test]
+    fn table_lists_every_process() {
+        let processes = [
+            ProcessBuilder::new(Pid(1))
+                .owner("root")
+                .cmdline("init")
+                .build(),
+            ProcessBuilder::new(Pid(1234))
+                .owner("alice")
+                .cmdline("sleep\u{0}60\0")
+                .state(ProcessState::Sleeping)
+                .build(),
+        ];
+        let table = ProcessTable::new(&processes).to_string();
+        let rows: Vec<&str> = table.lines().skip(1).collect();
+        assert_eq!(rows.len(), 2);
+        assert!(rows[0].starts_with("1    root "));
+        assert!(rows[1].starts_with("1234 alice"));
+        assert!(rows[1].contains("S (sleeping)"));
+        assert!(rows[1].ends_with("sleep 60"));
+    }
+
+    #[test]
+    fn default_columns_come_from_fields() {
+        for column in Column::FIELDS {
+            assert_eq!(column.name().parse::<Column>().unwrap(), column);
+        }
+        let processes = [ProcessBuilder::new(Pid(1)).build()];
+        let header = ProcessTable::new(&processes).to_string();
+        let header: Vec<&str> = header.lines().next().unwrap().split_whitespace().collect();
+        assert_eq!(header.first(), Some(&"PID"));
+        assert_eq!(header.last(), Some(&"Cmdline"));
+        assert!(!header.contains(&"PPID") && !header.contains(&"%CPU"));
+    }
+
+    #[test]
+    fn table_without_columns_writes_empty_rows() {
+        let processes = [ProcessBuilder::new(Pid(1)).build()];
+        let table = ProcessTable::new(&processes).columns(&[]).to_string();
+        assert_eq!(table, "\n\n");
+    }
+
+    #[test]
+    fn scan_reads_pgid_and_sid_from_stat() {
+        let root = fixture_root("pgid-sid");
+        std::fs::create_dir(root.join("42")).unwrap();
+        std::fs::write(
+            root.join("42/stat"),
+            STAT.replacen("S 1 42 42", "S 1 40 30", 1),
+        )
+        .unwrap();
+
+        let (processes, _) = ProcScanner::with_root(&root).scan_with_errors().unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(processes[0].ppid(), Some(Pid(1)));
+        assert_eq!(processes[0].pgid(), Some(Pid(40)));
+        assert_eq!(processes[0].sid(), Some(Pid(30)));
+    }
+
+    #[test]
+    fn pid_rejects_zero_and_pid_max() {
+        let max = pid_max();
+        assert_eq!(Pid::new(0), None);
+        assert_eq!(Pid::new(1), Some(Pid(1)));
+        assert_eq!(Pid::new(max - 1), Some(Pid(max - 1)));
+        assert_eq!(Pid::new(max), None);
+        assert_eq!(Pid::new(u32::MAX), None);
+
+        assert_eq!("42".parse::<Pid>().unwrap(), Pid(42));
+        for pid in ["0", "-1", "abc", "", &max.to_string()] {
+            assert!(
+                matches!(pid.parse::<Pid>(), Err(PsError::InvalidPid(_))),
+                "{pid}"
+            );
+        }
+    }
+
+    #[test]
+    fn proc_stat_parses_comm_with_parentheses() {
+        let stat = "1234 ((a) b)) R 1 1234 1234 34816 1234 4194560 500 10 2 0 77 12 0 0 20 -5 \
+            3 0 98765 12345678 2048 18446744073709551615 1 1 0 0 0 0 0 4096 0 0 0 0 17 3 0 5";
+        let stat = ProcStat::parse(stat).unwrap();
+        assert_eq!(stat.pid, 1234);
+        assert_eq!(stat.comm, "(a) b)");
+        assert_eq!(stat.state, 'R');
+        assert_eq!(stat.ppid, 1);
+        assert_eq!(stat.utime, 77);
+        assert_eq!(stat.stime, 12);
+        assert_eq!(stat.nice, -5);
+        assert_eq!(stat.starttime, 98765);
+        assert_eq!(stat.vsize, 12_345_678);
+        assert_eq!(stat.rss, 2048);
+        assert_eq!(stat.processor, 3);
+        assert_eq!(stat.policy, 5);
+
+        assert!(matches!(
+            ProcStat::parse("1234 (sleep S 1 1234 1234"),
+            Err(PsError::MalformedStat)
+        ));
+    }
+
+    // The fixture words are as printed by a little-endian kernel.
+    #[cfg(target_endian = "little")]
+    #[test]
+    fn parse_net_table_reads_tcp_and_tcp6() {
+        let tcp = "  sl  local_address rem_address   st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n\
+            \x20  0: 0100007F:0035 00000000:0000 0A 00000000:00000000 00:00000000 00000000   101        0 21342 1 0000000000000000 100 0 0 10 0\n";
+        let connections = parse_net_table(Proto::Tcp, tcp).unwrap();
+        assert_eq!(
+            connections,
+            [NetConnection {
+                proto: Proto::Tcp,
+                local_addr: "127.0.0.1:53".parse().unwrap(),
+                remote_addr: "0.0.0.0:0".parse().unwrap(),
+                state: TcpState::Listen,
+                inode: 21342,
+            }]
+        );
+
+        let tcp6 = "  sl  local_address                         remote_address                        st tx_queue rx_queue tr tm->when retrnsmt   uid  timeout inode\n\
+            \x20  0: 00000000000000000000000001000000:1F90 00000000000000000000000001000000:D431 01 00000000:00000000 00:00000000 00000000  1000        0 45678 1 0000000000000000 20 4 30 10 -1\n";
+        let connections = parse_net_table(Proto::Tcp6, tcp6).unwrap();
+        assert_eq!(connections[0].local_addr, "[::1]:8080".parse().unwrap());
+        assert_eq!(connections[0].remote_addr, "[::1]:54321".parse().unwrap());
+        assert_eq!(connections[0].state, TcpState::Established);
+        assert_eq!(connections[0].inode, 45678);
+
+        // A partial trailing word used to be dropped silently.
+        assert!(matches!(
+            parse_net_addr("0100007F0:0035"),
+            Err(PsError::MalformedNetEntry)
+        ));
+    }
+
+    #[test]
+    fn parse_stat_fields_rejects_truncated_stat() {
+        let fields = parse_stat_fields(STAT).unwrap();
+        assert_eq!(fields.ppid, 1);
+        assert_eq!(fields.pgrp, 42);
+
+        // Cut off before `starttime`, like a short read.
+        let truncated: Vec<&str> = STAT.split(' ').take(21).collect();
+        assert!(matches!(
+            parse_stat_fields(&truncated.join(" ")),
+            Err(PsError::MalformedStat)
+        ));
+    }
+
+    #[test]
+    fn scan_reads_context_switches_from_status() {
+        let root = fixture_root("ctxt-switches");
+        std::fs::create_dir(root.join("42")).unwrap();
+        std::fs::write(root.join("42/stat"), STAT).unwrap();
+        std::fs::write(
+            root.join("42/status"),
+            "Name:\tbash\nState:\tS (sleeping)\nvoluntary_ctxt_switches:\t150\n\
+             nonvoluntary_ctxt_switches:\t7\n",
+        )
+        .unwrap();
+
+        let (processes, _) = ProcScanner::with_root(&root).scan_with_errors().unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        assert_eq!(processes[0].voluntary_ctx_switches(), Some(150));
+        assert_eq!(processes[0].nonvoluntary_ctx_switches(), Some(7));
+    }
+
+    #[test]
+    fn tree_nests_children_and_breaks_cycles() {
+        let process = |pid, ppid, name: &str| Process {
+            ppid: Some(Pid(ppid)),
+            name: Some(name.to_owned()),
+            ..Process::empty(Pid(pid))
+        };
+        let processes = [
+            Process {
+                ppid: None,
+                ..process(1, 0, "init")
+            },
+            process(10, 1, "sshd"),
+            process(11, 10, "bash"),
+            process(12, 1, "cron"),
+            // Parent exited, becomes a root.
+            process(20, 99, "orphan"),
+            // Parents of each other.
+            process(30, 31, "a"),
+            process(31, 30, "b"),
+        ];
+
+        let tree = ProcessTree::new(&processes).to_string();
+        assert_eq!(
+            tree,
+            "1 init\n├─ 10 sshd\n│  └─ 11 bash\n└─ 12 cron\n20 orphan\n30 a\n└─ 31 b\n"
+        );
+    }
+
+    #[test]
+    fn same_process_needs_close_start_time() {
+        let start = Local::now();
+        let process = |pid, start_time| Process {
+            start_time: Some(start_time),
+            ..Process::empty(Pid(pid))
+        };
+        let a = process(42, start);
+
+        assert!(a.is_same_process_as(&process(42, start + chrono::TimeDelta::milliseconds(900))));
+        assert!(!a.is_same_process_as(&process(42, start + chrono::TimeDelta::seconds(5))));
+        assert!(!a.is_same_process_as(&process(43, start)));
+    }
+
+    #[test]
+    fn find_io_bytes_reads_storage_counters() {
+        let io = "rchar: 323934931\nwchar: 323929600\nsyscr: 632687\nsyscw: 632675\n\
+            read_bytes: 4096\nwrite_bytes: 323932160\ncancelled_write_bytes: 0\n";
+        assert_eq!(find_io_bytes(io), Some((4096, 323932160)));
+        assert_eq!(find_io_bytes("rchar: 1\nwchar: 2\n"), None);
+    }
+
+    #[test]
+    fn scan_detects_deleted_binary() {
+        let root = fixture_root("deleted-binary");
+        for (pid, exe) in [("42", "/usr/bin/app (deleted)"), ("43", "/usr/bin/app")] {
+            std::fs::create_dir(root.join(pid)).unwrap();
+            std::fs::write(root.join(pid).join("stat"), STAT.replacen("42", pid, 1)).unwrap();
+            std::os::unix::fs::symlink(exe, root.join(pid).join("exe")).unwrap();
+        }
+
+        let (processes, _) = ProcScanner::with_root(&root).scan_with_errors().unwrap();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let binaries: Vec<_> = processes
+            .iter()
+            .map(|process| (process.binary_path(), process.binary_deleted()))
+            .collect();
+        let app = Some(Path::new("/usr/bin/app"));
+        assert_eq!(binaries, [(app, true), (app, false)]);
+        assert_eq!(
+            Column::BinaryPath.cell(&processes[0], CellFormat::default()),
+            "/usr/bin/app (deleted)"
+        );
+    }
+
+    #[test]
+    fn cmdline_truncated_counts_chars() {
+        let process = ProcessBuilder::new(Pid(42))
+            .cmdline("grep\0héllo wörld\0")
+            .build();
+        // "grep héllo wörld" is 16 characters but 18 bytes.
+        assert_eq!(process.cmdline_truncated(16), "grep héllo wörld");
+        assert_eq!(process.cmdline_truncated(15), "grep héllo wör…");
+        assert_eq!(process.cmdline_truncated(8), "grep hé…");
+        assert_eq!(process.cmdline_truncated(1), "…");
+        assert_eq!(process.cmdline_truncated(0), "");
+        assert_eq!(Process::empty(Pid(1)).cmdline_truncated(10), "-");
+    }
+
+    #[test]
+    fn control_characters_are_escaped() {
+        let process = ProcessBuilder::new(Pid(42))
+            .cmdline("evil\0\x1b[2J\0")
+            .build();
+        let row = process.to_string();
+        assert!(!row.contains('\x1b'));
+        assert!(row.contains("evil \\x1b[2J"));
+        let table = ProcessTable::new(std::slice::from_ref(&process)).to_string();
+        assert!(!table.contains('\x1b'));
+        // `comm` can be set to anything with `prctl(PR_SET_NAME)`.
+        let renamed = Process {
+            name: Some("evil\x1b[2J".to_owned()),
+            ..process.clone()
+        };
+        let tree = ProcessTree::new(std::slice::from_ref(&renamed)).to_string();
+        assert!(!tree.contains('\x1b'));
+        assert!(tree.contains("42 evil\\x1b[2J"));
+        // The raw accessor keeps the original bytes.
+        assert_eq!(process.argv(), Some(vec!["evil", "\x1b[2J"]));
+    }
+
+    #[test]
+    fn find_state_tab_separated() {
+        let status = "Name:\tbash\nUmask:\t0022\nState:\tS (sleeping)\nTgid:\t42\n";
+        assert_eq!(
+            parse_state_from_status(status).as_deref(),
+            Some("S (sleeping)")
+        );
+        assert_eq!(
+            ProcStatus::parse(status).state,
+            Some(ProcessState::Sleeping)
+        );
+    }
+
+    #[test]
+    fn find_state_space_separated() {
+        let status = "Name:   bash\nUmask:  0022\nState:  R (running)\nTgid:   42\n";
+        assert_eq!(
+            parse_state_from_status(status).as_deref(),
+            Some("R (running)")
+        );
+        assert_eq!(ProcStatus::parse(status).state, Some(ProcessState::Running));
     }
-    Ok(vec_of_processs)
 }
@@ -1,13 +1,523 @@
 #![warn(clippy::pedantic)]
+
+use std::{
+    cmp::Ordering,
+    io::{self, IsTerminal, Write},
+    sync::atomic::{self, AtomicBool},
+    time::{Duration, Instant},
+};
+
+/// When to colorize the process rows.
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum Color {
+    /// Only when stdout is a terminal.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+/// How the process list is printed.
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    /// Aligned columns for reading.
+    #[default]
+    Text,
+    Csv,
+    /// A JSON array of process objects.
+    Json,
+    /// One JSON object per line.
+    Ndjson,
+}
+
+/// Command line options for the binary.
+// Every flag is a bool, that's fine for a flat list of switches.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Default)]
+struct Args {
+    /// Print memory columns as exact byte counts instead of e.g. `12.3M`.
+    bytes: bool,
+    /// Print processes grouped under a per-user header with totals.
+    group_by_user: bool,
+    /// Only print the number of matching processes.
+    count: bool,
+    /// Only show processes owned by this user.
+    user: Option<String>,
+    /// Only show processes with this executable name.
+    name: Option<String>,
+    /// Only show the first this many processes.
+    limit: Option<usize>,
+    /// Only show the process with this PID.
+    pid: Option<ps::Pid>,
+    /// Only show processes using at least / at most this many bytes of RSS.
+    min_mem: Option<u64>,
+    max_mem: Option<u64>,
+    /// Only show processes started less than this long ago.
+    since: Option<Duration>,
+    /// Reprint the list with this interval until interrupted.
+    watch: Option<Duration>,
+    /// Keys to sort by in order of priority, `true` for descending.
+    sort: Vec<(ps::SortKey, bool)>,
+    /// Read the kernel function each process is blocked in.
+    wchan: bool,
+    /// Add the process group and session ID columns.
+    jobs: bool,
+    /// Leave out the header line, for scripts.
+    no_header: bool,
+    /// Print start times in UTC instead of the local timezone.
+    utc: bool,
+    /// Read the OOM killer score and adjustment of each process.
+    oom: bool,
+    /// Count the open file descriptors of each process.
+    fds: bool,
+    /// Resolve the working directory of each process.
+    cwd: bool,
+    /// Print a row per thread.
+    threads: bool,
+    /// Print processes indented under their parents.
+    tree: bool,
+    /// Only print zombies and the parents that haven't reaped them.
+    zombies: bool,
+    color: Color,
+    format: Format,
+    /// Print each process as a short summary line instead of a table row.
+    compact: bool,
+    /// Join the text format's fields with this instead of padding them.
+    delimiter: Option<char>,
+    /// Exactly these columns, from `--fields`.
+    fields: Option<Vec<ps::Column>>,
+    /// `--fields` without a list prints the supported column names.
+    list_fields: bool,
+}
+
+impl Args {
+    // One match arm per flag, splitting them up wouldn't make it easier to read.
+    #[allow(clippy::too_many_lines)]
+    fn parse() -> Result<Self, String> {
+        let mut options = Self::default();
+        let mut args = std::env::args().skip(1).peekable();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--human" => options.bytes = false,
+                "--bytes" => options.bytes = true,
+                "--group-by" => match args.next().as_deref() {
+                    Some("user") => options.group_by_user = true,
+                    Some(key) => {
+                        return Err(format!("Unknown group-by key: {key} (expected user)"));
+                    }
+                    None => return Err("--group-by requires a key (user)".to_owned()),
+                },
+                "--color" => {
+                    options.color = match args.next().as_deref() {
+                        Some("auto") => Color::Auto,
+                        Some("always") => Color::Always,
+                        Some("never") => Color::Never,
+                        _ => return Err("--color expects auto, always or never".to_owned()),
+                    }
+                }
+                "--format" => {
+                    options.format = match args.next().as_deref() {
+                        Some("text") => Format::Text,
+                        Some("csv") => Format::Csv,
+                        Some("json") => Format::Json,
+                        Some("ndjson") => Format::Ndjson,
+                        _ => {
+                            return Err("--format expects text, csv, json or ndjson".to_owned());
+                        }
+                    }
+                }
+                "--delimiter" => {
+                    let delimiter = args.next().ok_or("--delimiter requires a character")?;
+                    // Accept a literal `\t` since typing a tab is awkward.
+                    let mut chars = delimiter.chars();
+                    options.delimiter = match (delimiter.as_str(), chars.next(), chars.next()) {
+                        ("\\t", ..) => Some('\t'),
+                        // Unquoted commas in the command line would be ambiguous.
+                        (",", ..) => {
+                            return Err("Invalid --delimiter: , (use --format csv for \
+                                        comma-separated output)"
+                                .to_owned());
+                        }
+                        (_, Some(delimiter), None) => Some(delimiter),
+                        _ => {
+                            return Err(format!(
+                                "Invalid --delimiter: {delimiter} (expected one character)"
+                            ));
+                        }
+                    };
+                }
+                "--fields" => match args.next_if(|fields| !fields.starts_with("--")) {
+                    Some(fields) => {
+                        options.fields = Some(
+                            fields
+                                .split(',')
+                                .map(parse_field)
+                                .collect::<Result<_, _>>()?,
+                        );
+                    }
+                    None => options.list_fields = true,
+                },
+                "--count" => options.count = true,
+                "--compact" => options.compact = true,
+                "--wchan" => options.wchan = true,
+                "--oom" => options.oom = true,
+                "--cwd" => options.cwd = true,
+                "--fds" => options.fds = true,
+                "--utc" => options.utc = true,
+                "--no-header" => options.no_header = true,
+                "--jobs" => options.jobs = true,
+                "--zombies" => options.zombies = true,
+                "--tree" => options.tree = true,
+                "--threads" => options.threads = true,
+                "--user" => options.user = Some(args.next().ok_or("--user requires a username")?),
+                "--name" => options.name = Some(args.next().ok_or("--name requires a name")?),
+                "--limit" => {
+                    let limit = args.next().ok_or("--limit requires a number")?;
+                    options.limit = Some(
+                        limit
+                            .parse()
+                            .map_err(|_| format!("Invalid --limit: {limit}"))?,
+                    );
+                }
+                "--watch" => {
+                    let seconds = args.next().ok_or("--watch requires a number of seconds")?;
+                    options.watch = Some(
+                        seconds
+                            .parse()
+                            .ok()
+                            .filter(|&seconds| seconds > 0)
+                            .map(Duration::from_secs)
+                            .ok_or_else(|| format!("Invalid --watch: {seconds}"))?,
+                    );
+                }
+                "--min-mem" => {
+                    let size = args.next().ok_or("--min-mem requires a size")?;
+                    options.min_mem = Some(parse_size(&size)?);
+                }
+                "--max-mem" => {
+                    let size = args.next().ok_or("--max-mem requires a size")?;
+                    options.max_mem = Some(parse_size(&size)?);
+                }
+                "--since" => {
+                    let since = args.next().ok_or("--since requires a duration")?;
+                    options.since = Some(parse_duration(&since)?);
+                }
+                "--sort" => {
+                    let keys = args.next().ok_or("--sort requires a key")?;
+                    options.sort = keys
+                        .split(',')
+                        .map(parse_sort_key)
+                        .collect::<Result<_, _>>()?;
+                }
+                "--pid" => {
+                    let pid = args.next().ok_or("--pid requires a PID")?;
+                    options.pid = Some(pid.parse().map_err(|_| format!("Invalid --pid: {pid}"))?);
+                }
+                _ => return Err(format!("Unknown argument: {arg}")),
+            }
+        }
+        Ok(options)
+    }
+}
+
+/// Parses a memory size like `512K`, `100M` or `2G` into bytes, in binary
+/// multiples like the RSS column. A bare number is a byte count.
+fn parse_size(size: &str) -> Result<u64, String> {
+    let invalid = || format!("Invalid size: {size} (expected e.g. 512K, 100M or 2G)");
+    let (count, multiplier) = match size.char_indices().last() {
+        Some((i, 'K' | 'k')) => (&size[..i], 1 << 10),
+        Some((i, 'M' | 'm')) => (&size[..i], 1 << 20),
+        Some((i, 'G' | 'g')) => (&size[..i], 1 << 30),
+        _ => (size, 1),
+    };
+    let count: u64 = count.parse().map_err(|_| invalid())?;
+    count.checked_mul(multiplier).ok_or_else(invalid)
+}
+
+/// Parses a `--since` duration like `30s`, `5m`, `2h` or `1d`.
+fn parse_duration(duration: &str) -> Result<Duration, String> {
+    let invalid = || format!("Invalid duration: {duration} (expected e.g. 30s, 5m, 2h or 1d)");
+    let split = duration.len() - duration.chars().last().map_or(0, char::len_utf8);
+    let (count, unit) = duration.split_at(split);
+    let count: u64 = count.parse().map_err(|_| invalid())?;
+    let unit_seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        _ => return Err(invalid()),
+    };
+    count
+        .checked_mul(unit_seconds)
+        .map(Duration::from_secs)
+        .ok_or_else(invalid)
+}
+
+/// Parses a `--fields` column name like `pid` or `rss`.
+fn parse_field(name: &str) -> Result<ps::Column, String> {
+    name.parse().map_err(|_| {
+        let names: Vec<&str> = ps::Column::FIELDS
+            .iter()
+            .map(|column| column.name())
+            .collect();
+        format!(
+            "Unknown field: {name} (expected one of: {})",
+            names.join(", ")
+        )
+    })
+}
+
+/// Parses a `--sort` key like `cpu` or `-mem`, a leading `-` sorts descending.
+fn parse_sort_key(key: &str) -> Result<(ps::SortKey, bool), String> {
+    let (name, descending) = match key.strip_prefix('-') {
+        Some(name) => (name, true),
+        None => (key, false),
+    };
+    let key = match name {
+        "pid" => ps::SortKey::Pid,
+        "cpu" => ps::SortKey::Cpu,
+        "mem" => ps::SortKey::Mem,
+        "name" => ps::SortKey::Name,
+        _ => {
+            return Err(format!(
+                "Unknown sort key: {name} (expected pid, cpu, mem or name)"
+            ));
+        }
+    };
+    Ok((key, descending))
+}
+
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    match try_main() {
+        // The reader went away, e.g. `ps | head`, so there's no one to tell.
+        Err(e)
+            if e.downcast_ref::<io::Error>()
+                .is_some_and(|e| e.kind() == io::ErrorKind::BrokenPipe) =>
+        {
+            Ok(())
+        }
+        res => res,
+    }
+}
+
+fn try_main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse()?;
 
     // Use a boxed error (heap pointer) because we don't know the type (and so compiler doesn't know its size).
     // `Box<dyn Error>` allows returning any error that implements `std::error::Error`.
-    let processes = ps::get_processes()?;
+    if let Some(user) = &args.user
+        && !user_exists(user)
+    {
+        return Err(format!("Unknown user: {user}").into());
+    }
+
+    if args.list_fields {
+        let mut out = io::stdout().lock();
+        for column in ps::Column::FIELDS {
+            writeln!(out, "{}", column.name())?;
+        }
+        return Ok(());
+    }
+
+    let Some(interval) = args.watch else {
+        return run(&args);
+    };
+    // Exit the loop on Ctrl-C instead of being killed halfway through a
+    // frame, which could leave a colored row unterminated.
+    unsafe {
+        libc::signal(libc::SIGINT, on_sigint as *const () as libc::sighandler_t);
+    }
+    let clear = io::stdout().is_terminal();
+    while !INTERRUPTED.load(atomic::Ordering::Relaxed) {
+        if clear {
+            // Clear the screen and move the cursor to the top left.
+            write!(io::stdout(), "\x1b[2J\x1b[H")?;
+        }
+        run(&args)?;
+        io::stdout().flush()?;
+        // Sleep in short steps so Ctrl-C doesn't wait out the interval.
+        let deadline = Instant::now() + interval;
+        while !INTERRUPTED.load(atomic::Ordering::Relaxed) && Instant::now() < deadline {
+            std::thread::sleep(WATCH_POLL.min(deadline - Instant::now()));
+        }
+    }
+    Ok(())
+}
+
+/// Set by the `SIGINT` handler during `--watch`.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// How often `--watch` checks for Ctrl-C while waiting.
+const WATCH_POLL: Duration = Duration::from_millis(100);
+
+extern "C" fn on_sigint(_: libc::c_int) {
+    // Only async-signal-safe work here, the loop does the rest.
+    INTERRUPTED.store(true, atomic::Ordering::Relaxed);
+}
+
+/// Scans, filters and prints the processes once.
+fn run(args: &Args) -> Result<(), Box<dyn std::error::Error>> {
+    // Selecting an opt-in column with `--fields` reads it as well.
+    let selected = |column| args.fields.as_ref().is_some_and(|f| f.contains(&column));
+    let scanner = ps::ProcScanner::new()
+        .wchan(args.wchan || selected(ps::Column::Wchan))
+        .cwd(args.cwd || selected(ps::Column::Cwd))
+        .fds(args.fds || selected(ps::Column::NumFd))
+        .threads(args.threads);
+    let (mut processes, warnings) = match args.pid {
+        Some(pid) => match scanner.process_with_errors(pid) {
+            Ok((process, warnings)) => (vec![process], warnings),
+            Err(ps::PsError::ProcessGone(_)) => {
+                return Err(format!("No process with PID {pid}").into());
+            }
+            Err(e) => return Err(e.into()),
+        },
+        None => scanner.scan_with_errors()?,
+    };
+    for warning in warnings {
+        eprintln!("{warning}");
+    }
+    if let Some(user) = &args.user {
+        // Owners are shown by name, so look up a numeric UID first. Without a
+        // passwd entry the owner is the number itself.
+        let owner = match user.parse() {
+            Ok(uid) => ps::resolve_uid(uid).unwrap_or_else(|| user.clone()),
+            Err(_) => user.clone(),
+        };
+        processes.retain(|process| process.owner() == Some(owner.as_str()));
+        // `--count` prints 0 instead.
+        if processes.is_empty() && !args.count {
+            return Err(format!("No processes owned by {user}").into());
+        }
+    }
+    if let Some(name) = &args.name {
+        processes.retain(|process| process.name() == Some(name.as_str()));
+    }
+    if let Some(since) = args.since {
+        let cutoff = chrono::Duration::from_std(since)
+            .ok()
+            .and_then(|since| chrono::Local::now().checked_sub_signed(since));
+        // A cutoff before the representable range lets every process through.
+        if let Some(cutoff) = cutoff {
+            processes = ps::filter_started_after(processes, cutoff);
+        }
+    }
+    processes = ps::filter_by_rss_range(processes, args.min_mem, args.max_mem);
+    // `--sort cpu` depends on the current time, keep it fixed while sorting.
+    let now = chrono::Local::now();
+    processes.sort_by(|a, b| {
+        args.sort
+            .iter()
+            .map(|&(key, descending)| {
+                let ordering = a.cmp_by_at(b, key, now);
+                if descending {
+                    ordering.reverse()
+                } else {
+                    ordering
+                }
+            })
+            .fold(Ordering::Equal, Ordering::then)
+    });
+    if let Some(limit) = args.limit {
+        processes = ps::top_n(processes, limit);
+    }
 
-    for process in processes {
-        println!("{process}");
+    // Not println!, which panics once stdout is closed.
+    let mut out = io::stdout().lock();
+    if args.zombies {
+        if !args.no_header {
+            writeln!(out, "{:<10} {:<10}", "PID", "PPID")?;
+        }
+        for (zombie, parent) in ps::zombie_parents(&processes) {
+            writeln!(out, "{zombie:<10} {parent:<10}")?;
+        }
+    } else if args.tree {
+        write!(out, "{}", ps::ProcessTree::new(&processes))?;
+    } else if args.count {
+        writeln!(out, "{}", processes.len())?;
+    } else if args.group_by_user {
+        for group in ps::group_by_owner(processes) {
+            let summary = group.summary;
+            let total_rss = if args.bytes {
+                summary.total_rss.to_string()
+            } else {
+                ps::format_bytes(summary.total_rss)
+            };
+            writeln!(
+                out,
+                "{}: {} processes, {total_rss} RSS, {}s CPU ({:.1}%)",
+                group.owner,
+                summary.count,
+                summary.total_cpu_time.as_secs(),
+                summary.total_cpu_percent
+            )?;
+            print_processes(&group.processes, args, &mut out)?;
+        }
+    } else {
+        print_processes(&processes, args, &mut out)?;
     }
 
     Ok(())
 }
+
+fn print_processes(processes: &[ps::Process], args: &Args, out: &mut impl Write) -> io::Result<()> {
+    let color = match args.color {
+        _ if args.format != Format::Text => false,
+        Color::Auto => io::stdout().is_terminal(),
+        Color::Always => true,
+        Color::Never => false,
+    };
+
+    let mut table = ps::ProcessTable::new(processes)
+        .raw_bytes(args.bytes)
+        .utc(args.utc)
+        .job_columns(args.jobs)
+        .oom_columns(args.oom)
+        .header(!args.no_header);
+    if let Some(width) = terminal_width() {
+        table = table.max_width(width);
+    }
+    if let Some(fields) = &args.fields {
+        table = table.columns(fields);
+    }
+
+    match args.format {
+        Format::Text if args.compact => {
+            for process in processes {
+                writeln!(out, "{}", process.format_compact())?;
+            }
+            Ok(())
+        }
+        Format::Text if let Some(delimiter) = args.delimiter => {
+            table.write_delimited(out, delimiter)
+        }
+        Format::Text => ps::ProcessListDisplay::new(table)
+            .color(color)
+            .write_to(out),
+        Format::Csv => table.write_csv(out),
+        Format::Json => table.write_json(out),
+        Format::Ndjson => table.write_ndjson(out),
+    }
+}
+
+/// Whether `user` has a passwd entry. Owners without one show up as their
+/// numeric UID, so any number counts as well.
+fn user_exists(user: &str) -> bool {
+    if user.parse::<libc::uid_t>().is_ok() {
+        return true;
+    }
+    ps::resolve_username(user).is_some()
+}
+
+/// Width of the terminal on stdout, or `None` if it isn't one.
+fn terminal_width() -> Option<usize> {
+    let mut size = libc::winsize {
+        ws_row: 0,
+        ws_col: 0,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+    // TIOCGWINSZ only writes the window size into `size`.
+    let res = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &raw mut size) };
+    (res == 0 && size.ws_col > 0).then_some(usize::from(size.ws_col))
+}
@@ -0,0 +1,72 @@
+//! Scans the live `/proc` of the machine running the tests.
+
+use ps::{Pid, ProcScanner, get_processes};
+
+fn own_pid() -> Pid {
+    Pid::new(std::process::id()).expect("own PID is valid")
+}
+
+#[test]
+fn get_processes_lists_current_process() {
+    let processes = get_processes().expect("scanning /proc succeeds");
+
+    let own = processes
+        .iter()
+        .find(|process| process.pid() == own_pid())
+        .expect("current process is listed");
+    assert_eq!(own.pid().get(), std::process::id());
+    assert!(own.rss_bytes().is_some_and(|rss| rss > 0));
+    assert!(own.start_time().is_some());
+}
+
+#[test]
+fn get_processes_lists_init() {
+    let processes = get_processes().unwrap();
+    assert!(processes.iter().any(|process| process.pid().get() == 1));
+}
+
+#[test]
+fn scan_with_every_option_enabled() {
+    let processes = ProcScanner::new()
+        .wchan(true)
+        .cgroup(true)
+        .loginuid(true)
+        .cwd(true)
+        .fds(true)
+        .scan()
+        .unwrap();
+
+    let own = processes
+        .iter()
+        .find(|process| process.pid() == own_pid())
+        .unwrap();
+    assert!(own.oom_score().is_some());
+}
+
+#[test]
+fn current_process_reads_own_entries() {
+    let own = ps::current_process().unwrap();
+    assert_eq!(own.pid(), own_pid());
+    assert_eq!(own.binary_path(), std::env::current_exe().ok().as_deref());
+    assert!(!own.cmdline_is_empty());
+}
+
+#[test]
+fn get_process_by_pid_of_exited_process() {
+    let mut child = std::process::Command::new("true").spawn().unwrap();
+    let pid = Pid::new(child.id()).unwrap();
+    child.wait().unwrap();
+
+    assert!(matches!(
+        ps::get_process_by_pid(pid),
+        Err(ps::PsError::ProcessGone(gone)) if gone == pid
+    ));
+}
+
+#[test]
+fn passwd_lookups_round_trip_for_root() {
+    assert_eq!(ps::resolve_username("root"), Some(0));
+    assert_eq!(ps::resolve_uid(0).as_deref(), Some("root"));
+    assert_eq!(ps::resolve_username("no such user"), None);
+    assert_eq!(ps::resolve_username("nul\0byte"), None);
+}